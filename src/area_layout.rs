@@ -0,0 +1,243 @@
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AreaLayoutError {
+  AreaCount {
+    expected: usize,
+    found: usize,
+  },
+  NonPositiveArea(Node),
+  /// The solver failed to converge, and the rectangulation is not area-universal because the
+  /// maximal segment between these regions has more than one region on both of its sides, so the
+  /// areas may not be realisable. The solver can also fail on areas which are.
+  NotAreaUniversal {
+    low_side: Vec<Node>,
+    high_side: Vec<Node>,
+  },
+  /// The rectangulation is area-universal, but the solver failed to converge.
+  NoConvergence,
+  /// The solver converged, but to a layout which fails `validate_layout`, usually because the areas
+  /// can only be met by shrinking a wall between two regions to nothing.
+  Invalid(LayoutError),
+}
+
+/// Finds coordinates for the walls such that region `i` has area proportional to `areas[i]`,
+/// using a damped Newton iteration starting from the grid layout. This is expected to succeed when
+/// every maximal segment is one-sided (is entirely the side of some region), as the rectangulation
+/// is then area-universal, but the solver isn't guaranteed to converge even then. The layout is
+/// validated before it is returned.
+pub fn generate_area_layout(
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  areas: &[f64],
+) -> Result<Layout, AreaLayoutError> {
+  if areas.len() != div.num_regions() as usize {
+    return Err(AreaLayoutError::AreaCount {
+      expected: div.num_regions() as usize,
+      found: areas.len(),
+    });
+  }
+  if let Some(region) = div.regions().find(|r| {
+    let area = areas[r.0 as usize - 4];
+    area.is_nan() || area <= 0.
+  }) {
+    return Err(AreaLayoutError::NonPositiveArea(region));
+  }
+  let total = areas.iter().sum::<f64>();
+  let areas = areas.iter().map(|area| area / total).collect::<Vec<_>>();
+
+  let sides = region_sides(div, edge_labels);
+  let axes = [
+    AxisSegments::new(div, &sides, 1),
+    AxisSegments::new(div, &sides, 0),
+  ];
+  let mut coords = axes
+    .iter()
+    .map(|segments| {
+      let grid = segments.grid_coordinates();
+      let size = grid[segments.hi_border] as f64;
+      grid.iter().map(|&x| x as f64 / size).collect::<Vec<_>>()
+    })
+    .collect::<Vec<_>>();
+  // The borders are fixed, every other segment is a variable.
+  let vars = axes
+    .iter()
+    .enumerate()
+    .flat_map(|(axis, segments)| {
+      (0..segments.count)
+        .filter(move |&s| s != segments.lo_border && s != segments.hi_border)
+        .map(move |s| (axis, s))
+    })
+    .collect::<Vec<_>>();
+  let mut var_index = axes
+    .iter()
+    .map(|segments| vec![None; segments.count])
+    .collect::<Vec<_>>();
+  for (i, &(axis, s)) in vars.iter().enumerate() {
+    var_index[axis][s] = Some(i);
+  }
+
+  let residuals = |coords: &Vec<Vec<f64>>| {
+    (0..areas.len())
+      .map(|r| {
+        let [x1, x2] = axes[0].region_segments[r];
+        let [y1, y2] = axes[1].region_segments[r];
+        (coords[0][x2] - coords[0][x1]) * (coords[1][y2] - coords[1][y1]) - areas[r]
+      })
+      .collect::<Vec<_>>()
+  };
+  let sum_squares = |residuals: &[f64]| residuals.iter().map(|x| x * x).sum::<f64>();
+
+  let mut current = residuals(&coords);
+  let mut damping = 1e-3;
+  let mut converged = false;
+  for _ in 0..1000 {
+    if current.iter().all(|x| x.abs() < 1e-12) {
+      converged = true;
+      break;
+    }
+    // The Jacobian of the residuals with respect to the variables.
+    let mut jacobian = vec![vec![0.; vars.len()]; areas.len()];
+    for (r, row) in jacobian.iter_mut().enumerate() {
+      let [x1, x2] = axes[0].region_segments[r];
+      let [y1, y2] = axes[1].region_segments[r];
+      let width = coords[0][x2] - coords[0][x1];
+      let height = coords[1][y2] - coords[1][y1];
+      for &(axis, s, d) in &[
+        (0, x1, -height),
+        (0, x2, height),
+        (1, y1, -width),
+        (1, y2, width),
+      ] {
+        if let Some(i) = var_index[axis][s] {
+          row[i] += d;
+        }
+      }
+    }
+    let mut normal = vec![vec![0.; vars.len()]; vars.len()];
+    let mut gradient = vec![0.; vars.len()];
+    for (row, residual) in jacobian.iter().zip(&current) {
+      for i in 0..vars.len() {
+        gradient[i] -= row[i] * residual;
+        for j in 0..vars.len() {
+          normal[i][j] += row[i] * row[j];
+        }
+      }
+    }
+    let mut stepped = false;
+    while damping < 1e12 {
+      let mut matrix = normal.clone();
+      for (i, row) in matrix.iter_mut().enumerate() {
+        row[i] += damping * (1. + row[i]);
+      }
+      let step = match solve_linear(matrix, gradient.clone()) {
+        Some(step) => step,
+        None => {
+          damping *= 4.;
+          continue;
+        }
+      };
+      let mut next = coords.clone();
+      for (&(axis, s), d) in vars.iter().zip(&step) {
+        next[axis][s] += d;
+      }
+      let ordered = axes.iter().zip(&next).all(|(segments, coords)| {
+        segments
+          .order
+          .iter()
+          .all(|&(from, to)| coords[from] < coords[to])
+      });
+      if ordered {
+        let next_residuals = residuals(&next);
+        if sum_squares(&next_residuals) < sum_squares(&current) {
+          coords = next;
+          current = next_residuals;
+          damping = (damping / 3.).max(1e-12);
+          stepped = true;
+          break;
+        }
+      }
+      damping *= 4.;
+    }
+    if !stepped {
+      break;
+    }
+  }
+
+  if !converged {
+    for segments in &axes {
+      if let Some((low_side, high_side)) = two_sided_segment(segments) {
+        return Err(AreaLayoutError::NotAreaUniversal {
+          low_side,
+          high_side,
+        });
+      }
+    }
+    return Err(AreaLayoutError::NoConvergence);
+  }
+  let layout = (0..areas.len())
+    .map(|r| {
+      let [x1, x2] = axes[0].region_segments[r];
+      let [y1, y2] = axes[1].region_segments[r];
+      // The y axis runs up from the bottom.
+      Rect {
+        x1: coords[0][x1],
+        y1: 1. - coords[1][y2],
+        x2: coords[0][x2],
+        y2: 1. - coords[1][y1],
+      }
+    })
+    .collect();
+  validate_layout(div, edge_labels, &layout).map_err(AreaLayoutError::Invalid)?;
+  Ok(layout)
+}
+
+/// Returns the regions on each side of a maximal segment which has more than one region on both
+/// of its sides, if there is one.
+fn two_sided_segment(segments: &AxisSegments) -> Option<(Vec<Node>, Vec<Node>)> {
+  (0..segments.count)
+    .filter(|&s| s != segments.lo_border && s != segments.hi_border)
+    .map(|s| {
+      let side = |i: usize| {
+        segments
+          .region_segments
+          .iter()
+          .enumerate()
+          .filter(|(_, region_segments)| region_segments[i] == s)
+          .map(|(r, _)| Node::region(r as u16))
+          .collect::<Vec<_>>()
+      };
+      (side(1), side(0))
+    })
+    .find(|(low_side, high_side)| low_side.len() > 1 && high_side.len() > 1)
+}
+
+/// Solves `matrix * x = rhs` by Gaussian elimination with partial pivoting.
+fn solve_linear(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+  let n = rhs.len();
+  for col in 0..n {
+    let pivot = (col..n).max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))?;
+    if matrix[pivot][col].abs() < 1e-300 {
+      return None;
+    }
+    matrix.swap(col, pivot);
+    rhs.swap(col, pivot);
+    let (upper, lower) = matrix.split_at_mut(col + 1);
+    let pivot_row = &upper[col];
+    for (offset, row) in lower.iter_mut().enumerate() {
+      let factor = row[col] / pivot_row[col];
+      if factor != 0. {
+        for (x, pivot_x) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+          *x -= factor * pivot_x;
+        }
+        rhs[col + 1 + offset] -= factor * rhs[col];
+      }
+    }
+  }
+  let mut x = vec![0.; n];
+  for row in (0..n).rev() {
+    let sum = (row + 1..n).map(|k| matrix[row][k] * x[k]).sum::<f64>();
+    x[row] = (rhs[row] - sum) / matrix[row][row];
+  }
+  Some(x)
+}
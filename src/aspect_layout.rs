@@ -0,0 +1,229 @@
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AspectObjective {
+  /// Maximise the shortest side of any region.
+  MaxMinSide,
+  /// Minimise the largest ratio between the long and short side of any region.
+  MinMaxAspectRatio,
+}
+
+#[derive(Debug, Clone)]
+pub struct AspectLayout {
+  pub layout: Layout,
+  /// The shortest side or the largest aspect ratio, depending on the objective.
+  pub objective: f64,
+  /// Whether every region is within its bounds; when they can't all be met the optimiser still
+  /// minimises how far they are exceeded.
+  pub bounds_met: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AspectLayoutError {
+  BoundsCount {
+    expected: usize,
+    found: usize,
+  },
+  /// The optimised layout fails `validate_layout`.
+  Invalid(LayoutError),
+}
+
+/// How close a segment may be moved to the segments it must stay strictly between, well above the
+/// tolerance of `validate_layout`.
+const MIN_GAP: f64 = 1e-6;
+
+/// Moves the walls of the grid layout to optimise `objective`, one maximal segment at a time,
+/// until no single segment can be moved to improve it. `bounds` optionally gives the minimum and
+/// maximum width-to-height ratio of each region. The layout is validated before it is returned.
+pub fn generate_aspect_layout(
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  objective: AspectObjective,
+  bounds: Option<&[(f64, f64)]>,
+) -> Result<AspectLayout, AspectLayoutError> {
+  if let Some(bounds) = bounds {
+    if bounds.len() != div.num_regions() as usize {
+      return Err(AspectLayoutError::BoundsCount {
+        expected: div.num_regions() as usize,
+        found: bounds.len(),
+      });
+    }
+  }
+  let sides = region_sides(div, edge_labels);
+  let axes = [
+    AxisSegments::new(div, &sides, 1),
+    AxisSegments::new(div, &sides, 0),
+  ];
+  let mut coords = axes
+    .iter()
+    .map(|segments| {
+      let grid = segments.grid_coordinates();
+      let size = grid[segments.hi_border] as f64;
+      grid.iter().map(|&x| x as f64 / size).collect::<Vec<_>>()
+    })
+    .collect::<Vec<_>>();
+
+  let regions = div.num_regions() as usize;
+  let size = |coords: &Vec<Vec<f64>>, r: usize| {
+    let [x1, x2] = axes[0].region_segments[r];
+    let [y1, y2] = axes[1].region_segments[r];
+    (coords[0][x2] - coords[0][x1], coords[1][y2] - coords[1][y1])
+  };
+  // The largest aspect ratio or the inverse of the shortest side of each region, so that lower is
+  // better for both objectives.
+  let badness = |coords: &Vec<Vec<f64>>| {
+    (0..regions)
+      .map(|r| {
+        let (w, h) = size(coords, r);
+        match objective {
+          AspectObjective::MaxMinSide => 1. / w.min(h),
+          AspectObjective::MinMaxAspectRatio => (w / h).max(h / w),
+        }
+      })
+      .collect::<Vec<_>>()
+  };
+  let objective_value = |coords: &Vec<Vec<f64>>| {
+    let worst = badness(coords).into_iter().fold(0., f64::max);
+    match objective {
+      AspectObjective::MaxMinSide => 1. / worst,
+      AspectObjective::MinMaxAspectRatio => worst,
+    }
+  };
+  let violation = |coords: &Vec<Vec<f64>>| match bounds {
+    Some(bounds) => (0..regions)
+      .map(|r| {
+        let (w, h) = size(coords, r);
+        let (min, max) = bounds[r];
+        (min.ln() - (w / h).ln()).max(0.) + ((w / h).ln() - max.ln()).max(0.)
+      })
+      .sum::<f64>(),
+    None => 0.,
+  };
+  // Optimising the worst region directly stalls as soon as two regions are equally bad, so the
+  // p-norm of the badnesses is optimised first, with increasing p.
+  let cost = |coords: &Vec<Vec<f64>>, p: Option<f64>| {
+    let badness = badness(coords);
+    let worst = badness.iter().copied().fold(0., f64::max);
+    let norm = match p {
+      Some(p) => {
+        worst
+          * badness
+            .iter()
+            .map(|x| (x / worst).powf(p))
+            .sum::<f64>()
+            .powf(1. / p)
+      }
+      None => worst,
+    };
+    violation(coords) * 1e6 + norm
+  };
+
+  // The segments each segment must stay strictly between.
+  let neighbours = axes
+    .iter()
+    .map(|segments| {
+      let mut neighbours = vec![(vec![], vec![]); segments.count];
+      for &(from, to) in &segments.order {
+        neighbours[to].0.push(from);
+        neighbours[from].1.push(to);
+      }
+      neighbours
+    })
+    .collect::<Vec<_>>();
+
+  // Every stage starts from the best layout found so far, which the more exact stages can
+  // still make worse by their exact measure.
+  let mut best = coords.clone();
+  for &p in &[Some(2.), Some(8.), Some(32.), Some(128.), None] {
+    optimise(&axes, &neighbours, &mut coords, |coords| cost(coords, p));
+    if cost(&coords, None) < cost(&best, None) {
+      best = coords.clone();
+    }
+  }
+  let coords = best;
+
+  let layout: Layout = (0..regions)
+    .map(|r| {
+      let [x1, x2] = axes[0].region_segments[r];
+      let [y1, y2] = axes[1].region_segments[r];
+      // The y axis runs up from the bottom.
+      Rect {
+        x1: coords[0][x1],
+        y1: 1. - coords[1][y2],
+        x2: coords[0][x2],
+        y2: 1. - coords[1][y1],
+      }
+    })
+    .collect();
+  validate_layout(div, edge_labels, &layout).map_err(AspectLayoutError::Invalid)?;
+  Ok(AspectLayout {
+    layout,
+    objective: objective_value(&coords),
+    bounds_met: violation(&coords) == 0.,
+  })
+}
+
+/// Moves one segment at a time to its best position until a sweep over all segments no longer
+/// lowers `cost` noticeably.
+fn optimise(
+  axes: &[AxisSegments; 2],
+  neighbours: &[Vec<(Vec<usize>, Vec<usize>)>],
+  coords: &mut Vec<Vec<f64>>,
+  cost: impl Fn(&Vec<Vec<f64>>) -> f64,
+) {
+  let mut current = cost(coords);
+  for _ in 0..1000 {
+    let sweep_start = current;
+    for (axis, segments) in axes.iter().enumerate() {
+      for s in 0..segments.count {
+        if s == segments.lo_border || s == segments.hi_border {
+          continue;
+        }
+        let (lower, upper) = &neighbours[axis][s];
+        let lo = lower.iter().map(|&x| coords[axis][x]).fold(0., f64::max);
+        let hi = upper.iter().map(|&x| coords[axis][x]).fold(1., f64::min);
+        if hi - lo <= 2. * MIN_GAP {
+          continue;
+        }
+        // The cost needn't be unimodal in the position of a single segment, so the golden-section
+        // search may only find a local minimum, which is kept only if it lowers the cost.
+        let mut at = |t: f64| {
+          let old = coords[axis][s];
+          coords[axis][s] = t;
+          let cost = cost(coords);
+          coords[axis][s] = old;
+          cost
+        };
+        let ratio = (5f64.sqrt() - 1.) / 2.;
+        let (mut a, mut b) = (lo + MIN_GAP, hi - MIN_GAP);
+        let mut c = b - (b - a) * ratio;
+        let mut d = a + (b - a) * ratio;
+        let (mut fc, mut fd) = (at(c), at(d));
+        while b - a > 1e-9 {
+          if fc < fd {
+            b = d;
+            d = c;
+            fd = fc;
+            c = b - (b - a) * ratio;
+            fc = at(c);
+          } else {
+            a = c;
+            c = d;
+            fc = fd;
+            d = a + (b - a) * ratio;
+            fd = at(d);
+          }
+        }
+        let t = (a + b) / 2.;
+        let new_cost = at(t);
+        if new_cost < current {
+          coords[axis][s] = t;
+          current = new_cost;
+        }
+      }
+    }
+    if sweep_start - current < sweep_start.abs() * 1e-6 {
+      break;
+    }
+  }
+}
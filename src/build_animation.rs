@@ -0,0 +1,246 @@
+use std::fmt::Write;
+
+use crate::*;
+
+const SQUARE_SIZE: f64 = 100.;
+const PADDING: f64 = 10.;
+
+/// The divisions from `Division::default()` to a dissection along its generation path through
+/// `divide`, with the cut made at each step and a layout of each. Regions keep their numbers, so
+/// `r{i}` is the same room in every layout from the one with `i + 1` regions on, which is cut off
+/// by `cuts[i - 1]`.
+pub struct BuildSequence {
+  pub divs: Vec<Division>,
+  pub edge_labels: Vec<EdgeLabels>,
+  pub cuts: Vec<Cut>,
+  pub layouts: Vec<Layout>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildSequenceError {
+  /// Some division on the way back from `div` has no parent with a labelling, so `div` isn't
+  /// generated.
+  NotGenerated,
+  /// `divide` gives no division with the `child` hash from the one with the `parent` hash, though
+  /// it is the child's canonical parent.
+  NoCut { parent: u64, child: u64 },
+  /// The division reached has the hash of `div`, but isn't isomorphic to it.
+  HashCollision,
+}
+
+/// Follows the generation tree of `canonical_parent` to a division isomorphic to `div`, which ends
+/// up labelled like `edge_labels`. The labelling of each earlier division is the one agreeing with
+/// the next one's on the most edges, so the walls move as little as they can between layouts. The
+/// whole sequence is drawn in the canonical orientation of the dissection.
+pub fn build_sequence(
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  layout_fn: fn(&Division, &EdgeLabels) -> Layout,
+) -> Result<BuildSequence, BuildSequenceError> {
+  let mut hashes = vec![hash_division(div, None)];
+  let mut ancestor = canonical_parent(div);
+  while let Some(parent) = ancestor {
+    hashes.push(hash_division(&parent, None));
+    ancestor = canonical_parent(&parent);
+  }
+  if hashes.len() != div.num_regions() as usize {
+    return Err(BuildSequenceError::NotGenerated);
+  }
+  hashes.reverse();
+
+  let mut divs = vec![Division::default()];
+  let mut cuts = vec![];
+  for pair in hashes.windows(2) {
+    let (cut, child) = divide_with_cuts(divs.last().unwrap())
+      .find(|(_, child)| hash_division(child, None) == pair[1])
+      .ok_or(BuildSequenceError::NoCut {
+        parent: pair[0],
+        child: pair[1],
+      })?;
+    cuts.push(cut);
+    divs.push(child);
+  }
+
+  let last = divs.last().unwrap();
+  let isomorphism =
+    find_isomorphism(div, None, last, None).ok_or(BuildSequenceError::HashCollision)?;
+  let mut all_edge_labels = vec![edge_labels
+    .iter()
+    .map(|(&UnorderedPair(a, b), &label)| {
+      (
+        UnorderedPair(isomorphism.node(a), isomorphism.node(b)),
+        label != isomorphism.transform.swaps_orientation(),
+      )
+    })
+    .collect::<EdgeLabels>()];
+  for div in divs[..divs.len() - 1].iter().rev() {
+    let next = all_edge_labels.last().unwrap();
+    let (_, best) = label_edges(div)
+      .map(|edge_labels| {
+        let agreement = edge_labels
+          .iter()
+          .filter(|&(edge, label)| next.get(edge) == Some(label))
+          .count();
+        (agreement, edge_labels)
+      })
+      .reduce(|best, x| if x.0 > best.0 { x } else { best })
+      .ok_or(BuildSequenceError::NotGenerated)?;
+    all_edge_labels.push(best);
+  }
+  all_edge_labels.reverse();
+
+  let transform = canonical_transform(last, all_edge_labels.last());
+  let edge_labels = all_edge_labels
+    .iter()
+    .map(|edge_labels| edge_labels.transform(transform))
+    .collect::<Vec<_>>();
+  let cuts = cuts
+    .iter()
+    .zip(&divs)
+    .map(|(cut, div)| {
+      if !transform.reflected {
+        return Cut {
+          from: transform.node(cut.from),
+          to: transform.node(cut.to),
+          ..*cut
+        };
+      }
+      // Reflecting reverses clockwise order, so the new region runs from the end of the cut at `to`
+      // to the end at `from`, an unshared end being just clockwise of its node.
+      let end = |x: Node, share: bool| {
+        if share {
+          x
+        } else {
+          div[cut.region].get_item_after(x)
+        }
+      };
+      Cut {
+        region: cut.region,
+        from: transform.node(end(cut.to, cut.share_to)),
+        to: transform.node(end(cut.from, cut.share_from)),
+        share_from: cut.share_to,
+        share_to: cut.share_from,
+      }
+    })
+    .collect();
+  let divs = divs
+    .iter()
+    .map(|div| div.transform(transform))
+    .collect::<Vec<_>>();
+  let layouts = divs
+    .iter()
+    .zip(&edge_labels)
+    .map(|(div, edge_labels)| layout_fn(div, edge_labels))
+    .collect();
+  Ok(BuildSequence {
+    divs,
+    edge_labels,
+    cuts,
+    layouts,
+  })
+}
+
+impl BuildSequence {
+  /// The rect of region `r{region}` in layout `frame`, or before it is cut off, of the region it is
+  /// cut from.
+  fn rect(&self, region: usize, frame: usize) -> Rect {
+    if region <= frame {
+      self.layouts[frame][region]
+    } else {
+      self.rect((self.cuts[region - 1].region.0 - 4) as usize, frame)
+    }
+  }
+
+  /// An SVG of each layout, with each room in the same colour in all of them.
+  pub fn to_frame_svgs(&self) -> Vec<String> {
+    (0..self.layouts.len())
+      .map(|frame| {
+        let mut str = svg_start();
+        for (region, rect) in self.layouts[frame].iter().enumerate() {
+          write!(
+            str,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="black" stroke-width="2"/>"#,
+            rect.x1 * SQUARE_SIZE + PADDING,
+            rect.y1 * SQUARE_SIZE + PADDING,
+            rect.width() * SQUARE_SIZE,
+            rect.height() * SQUARE_SIZE,
+            colour(region),
+          )
+          .unwrap();
+        }
+        str += "</svg>";
+        str
+      })
+      .collect()
+  }
+
+  /// An SVG animating the layouts in a loop with SMIL, holding each for `seconds_per_cut` and then
+  /// taking as long to move to the next, with each new room growing out of the one it is cut from.
+  pub fn to_animated_svg(&self, seconds_per_cut: f64) -> String {
+    let frames = self.layouts.len();
+    let segments = 2 * frames - 1;
+    // Each layout is held from the time of one key to the next.
+    let key_times = (0..2 * frames)
+      .map(|i| format!("{}", i as f64 / segments as f64))
+      .collect::<Vec<_>>()
+      .join(";");
+    let mut str = svg_start();
+    for region in 0..frames {
+      let values = |value: &dyn Fn(Rect) -> f64| {
+        (0..2 * frames)
+          .map(|i| format!("{}", value(self.rect(region, i / 2))))
+          .collect::<Vec<_>>()
+          .join(";")
+      };
+      let rect = self.rect(region, 0);
+      write!(
+        str,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="black" stroke-width="2">"#,
+        rect.x1 * SQUARE_SIZE + PADDING,
+        rect.y1 * SQUARE_SIZE + PADDING,
+        rect.width() * SQUARE_SIZE,
+        rect.height() * SQUARE_SIZE,
+        colour(region),
+      )
+      .unwrap();
+      for (attribute, value) in [
+        ("x", values(&|rect| rect.x1 * SQUARE_SIZE + PADDING)),
+        ("y", values(&|rect| rect.y1 * SQUARE_SIZE + PADDING)),
+        ("width", values(&|rect| rect.width() * SQUARE_SIZE)),
+        ("height", values(&|rect| rect.height() * SQUARE_SIZE)),
+        (
+          "opacity",
+          (0..2 * frames)
+            .map(|i| if region <= i / 2 { "1" } else { "0" })
+            .collect::<Vec<_>>()
+            .join(";"),
+        ),
+      ] {
+        write!(
+          str,
+          r#"<animate attributeName="{}" values="{}" keyTimes="{}" dur="{}s" repeatCount="indefinite"/>"#,
+          attribute,
+          value,
+          key_times,
+          seconds_per_cut * segments as f64,
+        )
+        .unwrap();
+      }
+      str += "</rect>";
+    }
+    str += "</svg>";
+    str
+  }
+}
+
+fn svg_start() -> String {
+  format!(
+    r#"<svg viewBox="0 0 {0} {0}" xmlns="http://www.w3.org/2000/svg">"#,
+    SQUARE_SIZE + 2. * PADDING,
+  )
+}
+
+/// Hues a golden angle apart, so neighbouring numbers look different.
+fn colour(region: usize) -> String {
+  format!("hsl({:.1},70%,75%)", region as f64 * 137.508 % 360.)
+}
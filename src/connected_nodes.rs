@@ -1,23 +1,19 @@
 use crate::*;
-use std::{iter::FusedIterator, ptr};
+use std::{fmt::Debug, iter::FusedIterator, ptr};
 
-pub struct ConnectedNodes(u8);
+pub struct ConnectedNodes(u16);
 
 impl ConnectedNodes {
-  fn get_index(&self, item: Node) -> u8 {
-    for i in 0..self.len() {
-      if self.index(i) == item {
-        return i;
-      }
-    }
-    dbg!(item, self.len(), self);
-    panic!("item not in CircularOrder");
+  fn get_index(&self, item: Node) -> u16 {
+    self
+      .position(item)
+      .unwrap_or_else(|| panic!("{:?} is not in {:?}", item, self))
   }
-  fn index(&self, index: u8) -> Node {
+  fn index(&self, index: u16) -> Node {
     assert!(index < self.len());
     unsafe { *((self as *const _ as *const Node).offset((index + 1) as isize)) }
   }
-  fn index_mut(&mut self, index: u8) -> &mut Node {
+  fn index_mut(&mut self, index: u16) -> &mut Node {
     assert!(index < self.len());
     unsafe { &mut *((self as *mut _ as *mut Node).offset((index + 1) as isize)) }
   }
@@ -34,6 +30,8 @@ impl ConnectedNodes {
       self.index((i + self.len() - 1) % self.len()),
     )
   }
+  /// # Safety
+  /// There must be room for another item within the division's `max_connections`.
   pub unsafe fn insert_item_after(&mut self, item: Node, new_item: Node) {
     let i = self.get_index(item) + 1;
     let old_len = self.len();
@@ -78,23 +76,24 @@ impl ConnectedNodes {
       }
     }
   }
-  pub fn len(&self) -> u8 {
+  pub fn len(&self) -> u16 {
     self.0
   }
+  pub fn is_empty(&self) -> bool {
+    self.0 == 0
+  }
   pub fn iter(&self) -> CircularOrderIter<'_> {
-    CircularOrderIter(self, Some((0, 0)))
+    CircularOrderIter(self, if self.is_empty() { None } else { Some((0, 0)) })
   }
   pub fn iter_starting_at(&self, start: Node) -> CircularOrderIter<'_> {
     let i = self.get_index(start);
     CircularOrderIter(self, Some((i, i)))
   }
   pub fn contains_item(&self, item: Node) -> bool {
-    for i in 0..self.len() {
-      if self.index(i) == item {
-        return true;
-      }
-    }
-    false
+    self.position(item).is_some()
+  }
+  pub fn position(&self, item: Node) -> Option<u16> {
+    (0..self.len()).find(|&i| self.index(i) == item)
   }
 }
 
@@ -108,7 +107,7 @@ impl Debug for ConnectedNodes {
   }
 }
 
-pub struct CircularOrderIter<'a>(&'a ConnectedNodes, Option<(u8, u8)>);
+pub struct CircularOrderIter<'a>(&'a ConnectedNodes, Option<(u16, u16)>);
 
 impl<'a> FusedIterator for CircularOrderIter<'a> {}
 
@@ -1,6 +1,30 @@
 use crate::*;
 
+/// A way of dividing `region` in two with a new wall from its side with `from` to its side with
+/// `to`, the new region taking the nodes clockwise after `from` up to `to`. Each end of the wall
+/// is partway along the side if `share_from` or `share_to`, so both halves keep the node as a
+/// neighbour, and otherwise at the corner just clockwise of the side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cut {
+  pub region: Node,
+  pub from: Node,
+  pub to: Node,
+  pub share_from: bool,
+  pub share_to: bool,
+}
+
 pub fn divide<'a>(div: &'a Division) -> impl Iterator<Item = Division> + 'a {
+  divide_with_cuts(div).map(|(_, div)| div)
+}
+
+/// Like `divide`, but checks that the division is valid first.
+pub fn try_divide(div: &Division) -> Result<impl Iterator<Item = Division> + '_, Error> {
+  div.validate()?;
+  Ok(divide(div))
+}
+
+/// Like `divide`, along with the cut giving each division.
+pub fn divide_with_cuts<'a>(div: &'a Division) -> impl Iterator<Item = (Cut, Division)> + 'a {
   div.regions().flat_map(move |region| {
     let connected_nodes = &div[region];
     connected_nodes
@@ -28,20 +52,30 @@ pub fn divide<'a>(div: &'a Division) -> impl Iterator<Item = Division> + 'a {
                 Some(
                   [true, false]
                     .iter()
-                    .filter_map::<Division, _>(move |&share_1| {
+                    .filter_map::<(Cut, Division), _>(move |&share_1| {
                       if must_share_1 && !share_1 {
                         return None;
                       }
-                      return Some(_divide(
-                        div,
-                        connected_nodes,
+                      let cut = Cut {
                         region,
-                        cut_0_ind,
-                        cut_0,
-                        cut_1_ind,
-                        cut_1,
-                        share_0,
-                        share_1,
+                        from: cut_0,
+                        to: cut_1,
+                        share_from: share_0,
+                        share_to: share_1,
+                      };
+                      return Some((
+                        cut,
+                        _divide(
+                          div,
+                          connected_nodes,
+                          region,
+                          cut_0_ind,
+                          cut_0,
+                          cut_1_ind,
+                          cut_1,
+                          share_0,
+                          share_1,
+                        ),
                       ));
                     }),
                 )
@@ -69,11 +103,11 @@ fn _divide(
     || (share_1 && div[cut_1].len() == div.max_connections());
   unsafe {
     let mut new_div =
-      Division::new_raw(div.num_regions() + 1, div.max_connections() + expand as u8);
+      Division::new_raw(div.num_regions() + 1, div.max_connections() + expand as u16);
     for node in new_div.nodes() {
       let old_order = &div[if node == new_region { region } else { node }];
       let order = &mut new_div[node];
-      std::ptr::copy_nonoverlapping::<u8>(
+      std::ptr::copy_nonoverlapping::<u16>(
         old_order as *const _ as _,
         order as *mut _ as _,
         (div.max_connections() + 1) as usize,
@@ -8,20 +8,23 @@ use std::{
 use crate::*;
 
 pub struct Division {
-  regions: u8,
-  max_connections: u8,
-  ptr: NonNull<u8>,
+  regions: u16,
+  max_connections: u16,
+  ptr: NonNull<u16>,
 }
 
 unsafe impl Send for Division {}
 unsafe impl Sync for Division {}
 
 impl Division {
-  pub unsafe fn new_raw(regions: u8, max_connections: u8) -> Division {
+  /// # Safety
+  /// The data is left uninitialised, and each node's connected nodes must be written before the
+  /// division is used.
+  pub unsafe fn new_raw(regions: u16, max_connections: u16) -> Division {
     let size = Division::data_size(regions, max_connections);
-    let layout = alloc::Layout::array::<u8>(size).unwrap();
+    let layout = alloc::Layout::array::<u16>(size).unwrap();
     let ptr = alloc::alloc(layout);
-    let ptr = match NonNull::new(ptr) {
+    let ptr = match NonNull::new(ptr as *mut u16) {
       Some(p) => p,
       None => alloc::handle_alloc_error(layout),
     };
@@ -31,27 +34,101 @@ impl Division {
       ptr,
     }
   }
-  pub unsafe fn from_data(regions: u8, max_connections: u8, data: &[u8]) -> Division {
+  /// # Safety
+  /// Each node's slot of `data` must start with a number of connected nodes no greater than
+  /// `max_connections`.
+  pub unsafe fn from_data(regions: u16, max_connections: u16, data: &[u16]) -> Division {
     assert_eq!(data.len(), Division::data_size(regions, max_connections));
     let division = Division::new_raw(regions, max_connections);
     ptr::copy(data.as_ptr(), division.ptr.as_ptr(), data.len());
     division
   }
-  pub const fn data_size(regions: u8, max_connections: u8) -> usize {
+  /// Builds a division from the connected nodes of each node, in clockwise order, indexed by the
+  /// node's number. Only the numbers of nodes and connections are checked.
+  pub fn from_connected_nodes(connected_nodes: &[Vec<Node>]) -> Result<Division, Error> {
+    if connected_nodes.len() < 4 || connected_nodes.len() > u16::MAX as usize {
+      return Err(Error::NodeCount(connected_nodes.len()));
+    }
+    // A slot holds its length as well as the nodes.
+    if let Some(i) = connected_nodes
+      .iter()
+      .position(|nodes| nodes.len() >= u16::MAX as usize)
+    {
+      return Err(Error::TooManyConnections(Node(i as u16)));
+    }
+    let regions = (connected_nodes.len() - 4) as u16;
+    let max_connections = connected_nodes.iter().map(Vec::len).max().unwrap_or(0) as u16;
+    let mut data = vec![0; Division::data_size(regions, max_connections)];
+    for (i, nodes) in connected_nodes.iter().enumerate() {
+      let start = i * (max_connections + 1) as usize;
+      data[start] = nodes.len() as u16;
+      for (j, node) in nodes.iter().enumerate() {
+        data[start + 1 + j] = node.0;
+      }
+    }
+    Ok(unsafe { Division::from_data(regions, max_connections, &data) })
+  }
+  /// Like `from_connected_nodes`, but checks that the result is a valid division.
+  pub fn try_from_connected_nodes(connected_nodes: &[Vec<Node>]) -> Result<Division, Error> {
+    let div = Division::from_connected_nodes(connected_nodes)?;
+    div.validate()?;
+    Ok(div)
+  }
+  pub const fn data_size(regions: u16, max_connections: u16) -> usize {
     (regions + 4) as usize * (max_connections + 1) as usize
   }
-  pub fn num_regions(&self) -> u8 {
+  pub fn num_regions(&self) -> u16 {
     self.regions
   }
-  pub fn regions(&self) -> std::iter::Map<std::ops::Range<u8>, fn(u8) -> Node> {
+  pub fn regions(&self) -> std::iter::Map<std::ops::Range<u16>, fn(u16) -> Node> {
     (0..self.regions).map(Node::region)
   }
-  pub fn max_connections(&self) -> u8 {
+  pub fn max_connections(&self) -> u16 {
     self.max_connections
   }
-  pub fn nodes(&self) -> std::iter::Map<std::ops::Range<u8>, fn(u8) -> Node> {
+  pub fn nodes(&self) -> std::iter::Map<std::ops::Range<u16>, fn(u16) -> Node> {
     (0..self.regions + 4).map(Node)
   }
+  /// Like indexing, but without panicking if the node isn't in the division.
+  pub fn get(&self, node: Node) -> Result<&ConnectedNodes, Error> {
+    if node.0 < self.regions + 4 {
+      Ok(&self[node])
+    } else {
+      Err(Error::UnknownNode(node))
+    }
+  }
+  /// Like mutable indexing, but without panicking if the node isn't in the division.
+  pub fn get_mut(&mut self, node: Node) -> Result<&mut ConnectedNodes, Error> {
+    if node.0 < self.regions + 4 {
+      Ok(&mut self[node])
+    } else {
+      Err(Error::UnknownNode(node))
+    }
+  }
+  /// Like `get_item_after`, but without panicking if `item` isn't connected to `node`.
+  pub fn item_after(&self, node: Node, item: Node) -> Result<Node, Error> {
+    let connected_nodes = self.get(node)?;
+    connected_nodes
+      .position(item)
+      .map(|_| connected_nodes.get_item_after(item))
+      .ok_or(Error::NotConnected(node, item))
+  }
+  /// Like `get_item_before`, but without panicking if `item` isn't connected to `node`.
+  pub fn item_before(&self, node: Node, item: Node) -> Result<Node, Error> {
+    let connected_nodes = self.get(node)?;
+    connected_nodes
+      .position(item)
+      .map(|_| connected_nodes.get_item_before(item))
+      .ok_or(Error::NotConnected(node, item))
+  }
+  /// Like `get_items_around`, but without panicking if `item` isn't connected to `node`.
+  pub fn items_around(&self, node: Node, item: Node) -> Result<(Node, Node), Error> {
+    let connected_nodes = self.get(node)?;
+    connected_nodes
+      .position(item)
+      .map(|_| connected_nodes.get_items_around(item))
+      .ok_or(Error::NotConnected(node, item))
+  }
 }
 
 impl Default for Division {
@@ -66,6 +143,7 @@ impl Default for Division {
   }
 }
 
+/// Panics if the node isn't in the division; `get` doesn't.
 impl Index<Node> for Division {
   type Output = ConnectedNodes;
   fn index(&self, node: Node) -> &Self::Output {
@@ -80,6 +158,7 @@ impl Index<Node> for Division {
   }
 }
 
+/// Panics if the node isn't in the division; `get_mut` doesn't.
 impl IndexMut<Node> for Division {
   fn index_mut(&mut self, node: Node) -> &mut Self::Output {
     assert!(node.0 < self.regions + 4);
@@ -103,11 +182,38 @@ impl Debug for Division {
   }
 }
 
+/// Divisions are compared by their number of regions, then by each node's connected nodes in turn.
+impl Ord for Division {
+  fn cmp(&self, other: &Division) -> std::cmp::Ordering {
+    self.regions.cmp(&other.regions).then_with(|| {
+      self
+        .nodes()
+        .map(|node| self[node].iter().cmp(other[node].iter()))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+    })
+  }
+}
+
+impl PartialOrd for Division {
+  fn partial_cmp(&self, other: &Division) -> Option<std::cmp::Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl PartialEq for Division {
+  fn eq(&self, other: &Division) -> bool {
+    self.cmp(other) == std::cmp::Ordering::Equal
+  }
+}
+
+impl Eq for Division {}
+
 impl Drop for Division {
   fn drop(&mut self) {
     let size = Division::data_size(self.regions, self.max_connections);
-    let layout = alloc::Layout::array::<u8>(size).unwrap();
-    unsafe { alloc::dealloc(self.ptr.as_ptr(), layout) }
+    let layout = alloc::Layout::array::<u16>(size).unwrap();
+    unsafe { alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout) }
   }
 }
 
@@ -115,10 +221,10 @@ impl Drop for Division {
 macro_rules! division {
 
   ( $( $key:ident : [ $( $item:ident ),+ $(,)? ] ),+ $(,)? ) => {{
-    const REGIONS: u8 = division!(_ regions $([$($item)+])+);
-    const MAX_CONNECTIONS: u8 = division!(_ max_connections $([$($item)+])+);
+    const REGIONS: u16 = division!(_ regions $([$($item)+])+);
+    const MAX_CONNECTIONS: u16 = division!(_ max_connections $([$($item)+])+);
     const SIZE: usize = $crate::Division::data_size(REGIONS, MAX_CONNECTIONS);
-    const DATA: [u8; SIZE] = {
+    const DATA: [u16; SIZE] = {
       let mut data = [0; SIZE];
       division!( _ generate_data data MAX_CONNECTIONS $($key [$($item)*])*);
       data
@@ -0,0 +1,206 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::*;
+
+/// A 4-cycle of regions whose edges alternate between vertical and horizontal walls, and whose
+/// interior edges all meet each node of the cycle with the same label. The interior of such a
+/// cycle is a rectangle, and swapping the labels of the interior edges (a flip) turns it between
+/// its two pinwheel orientations.
+#[derive(Debug, Clone)]
+pub struct AlternatingCycle {
+  /// Clockwise, starting from the smallest node.
+  pub nodes: [Node; 4],
+  pub interior_edges: Vec<UnorderedPair<Node>>,
+  /// Whether the interior edges at each node of the cycle have the label of the edge to the next
+  /// node of the cycle, rather than the previous one.
+  pub clockwise: bool,
+}
+
+impl AlternatingCycle {
+  pub fn flip(&self, edge_labels: &mut EdgeLabels) {
+    for edge in &self.interior_edges {
+      let label = edge_labels.get_mut(edge).unwrap();
+      *label = !*label;
+    }
+  }
+}
+
+/// Finds the alternating 4-cycles of a labelling which have a non-empty interior, i.e. those which
+/// can be flipped.
+pub fn alternating_cycles(div: &Division, edge_labels: &EdgeLabels) -> Vec<AlternatingCycle> {
+  let mut cycles = vec![];
+  for a in div.regions() {
+    for b in div[a].iter().filter(|&b| b > a) {
+      for c in div[b].iter().filter(|&c| c > a) {
+        for d in div[c].iter().filter(|&d| d > a && d != b) {
+          if div[a].contains_item(d) {
+            cycles.extend(alternating_cycle(div, edge_labels, [a, b, c, d]));
+          }
+        }
+      }
+    }
+  }
+  cycles
+}
+
+fn alternating_cycle(
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  nodes: [Node; 4],
+) -> Option<AlternatingCycle> {
+  let label = |a: Node, b: Node| edge_labels[&UnorderedPair(a, b)];
+  if (0..4)
+    .any(|i| label(nodes[i], nodes[(i + 1) % 4]) == label(nodes[(i + 1) % 4], nodes[(i + 2) % 4]))
+  {
+    return None;
+  }
+  let mut interior_edges = HashSet::new();
+  let mut interior_nodes = vec![];
+  let mut clockwise = None;
+  for i in 0..4 {
+    let (prev, node, next) = (nodes[(i + 3) % 4], nodes[i], nodes[(i + 1) % 4]);
+    // Going clockwise around the cycle, the interior is clockwise from the next node.
+    let mut connected_node = div[node].get_item_after(next);
+    while connected_node != prev {
+      let interior_label = label(node, connected_node);
+      match clockwise {
+        None => clockwise = Some(interior_label == label(node, next)),
+        Some(clockwise) if clockwise != (interior_label == label(node, next)) => return None,
+        Some(_) => {}
+      }
+      interior_edges.insert(UnorderedPair(node, connected_node));
+      if !nodes.contains(&connected_node) {
+        interior_nodes.push(connected_node);
+      }
+      connected_node = div[node].get_item_after(connected_node);
+    }
+  }
+  let mut seen = interior_nodes.iter().copied().collect::<HashSet<_>>();
+  while let Some(node) = interior_nodes.pop() {
+    // If the borders are inside, the cycle was found anticlockwise.
+    if node.is_border() {
+      return None;
+    }
+    for connected_node in div[node].iter() {
+      interior_edges.insert(UnorderedPair(node, connected_node));
+      if !nodes.contains(&connected_node) && seen.insert(connected_node) {
+        interior_nodes.push(connected_node);
+      }
+    }
+  }
+  Some(AlternatingCycle {
+    nodes,
+    interior_edges: interior_edges.into_iter().collect(),
+    clockwise: clockwise?,
+  })
+}
+
+/// The four-way junctions of a division, as quadrilateral faces `[a, b, c, d]` of regions, clockwise
+/// from the smallest.
+pub fn four_way_junctions(div: &Division) -> Vec<[Node; 4]> {
+  let mut junctions = vec![];
+  for a in div.regions() {
+    for b in div[a].iter() {
+      let d = div[a].get_item_after(b);
+      let c = div[b].get_item_before(a);
+      if c != d && div[c].contains_item(d) && [b, c, d].iter().all(|&x| x > a) {
+        junctions.push([a, b, c, d]);
+      }
+    }
+  }
+  junctions
+}
+
+/// The labellings of a division don't form a lattice when it has four-way junctions, so each
+/// junction `[a, b, c, d]` is given the diagonal `a`-`c`. The labellings of the division are then
+/// those of the triangulated division whose junctions' edges alternate, with each diagonal labeled
+/// `true`.
+fn triangulate_four_way_junctions(div: &Division) -> (Division, Vec<[Node; 4]>) {
+  let junctions = four_way_junctions(div);
+  let mut connected_nodes = div
+    .nodes()
+    .map(|node| div[node].iter().collect::<Vec<_>>())
+    .collect::<Vec<_>>();
+  for &[a, b, c, d] in &junctions {
+    for &(node, before, diagonal) in &[(a, b, c), (c, d, a)] {
+      let nodes = &mut connected_nodes[node.0 as usize];
+      let i = nodes.iter().position(|&x| x == before).unwrap();
+      nodes.insert(i + 1, diagonal);
+    }
+  }
+  (
+    Division::from_connected_nodes(&connected_nodes).unwrap(),
+    junctions,
+  )
+}
+
+/// The bottom of the lattice of labellings. For a division without four-way junctions, this is the
+/// labelling with no clockwise alternating cycles.
+pub fn minimal_edge_labels(div: &Division) -> Option<EdgeLabels> {
+  edge_label_lattice(div).next()
+}
+
+pub struct EdgeLabelLatticeIter {
+  div: Division,
+  junctions: Vec<[Node; 4]>,
+  todo: VecDeque<EdgeLabels>,
+}
+
+/// Iterates over every labelling of a division, each exactly once and from the bottom of the
+/// lattice upwards, by flipping alternating cycles. Each labelling other than the minimal one is
+/// reached only from the labelling where its smallest clockwise cycle is flipped back, so no search
+/// or record of the labellings seen is needed.
+pub fn edge_label_lattice(div: &Division) -> EdgeLabelLatticeIter {
+  let (div, junctions) = triangulate_four_way_junctions(div);
+  let mut todo = VecDeque::new();
+  if let Some(mut edge_labels) = label_edges(&div).next() {
+    while let Some(cycle) = alternating_cycles(&div, &edge_labels)
+      .into_iter()
+      .find(|cycle| cycle.clockwise)
+    {
+      cycle.flip(&mut edge_labels);
+    }
+    todo.push_back(edge_labels);
+  }
+  EdgeLabelLatticeIter {
+    div,
+    junctions,
+    todo,
+  }
+}
+
+impl Iterator for EdgeLabelLatticeIter {
+  type Item = EdgeLabels;
+  fn next(&mut self) -> Option<EdgeLabels> {
+    while let Some(mut edge_labels) = self.todo.pop_front() {
+      for cycle in alternating_cycles(&self.div, &edge_labels) {
+        if cycle.clockwise {
+          continue;
+        }
+        let mut flipped = edge_labels.clone();
+        cycle.flip(&mut flipped);
+        let parent_cycle = alternating_cycles(&self.div, &flipped)
+          .into_iter()
+          .filter(|cycle| cycle.clockwise)
+          .map(|cycle| cycle.nodes)
+          .min();
+        if parent_cycle == Some(cycle.nodes) {
+          self.todo.push_back(flipped);
+        }
+      }
+      let label = |a: Node, b: Node| edge_labels[&UnorderedPair(a, b)];
+      if self.junctions.iter().all(|&[a, b, c, d]| {
+        label(a, c)
+          && label(a, b) != label(b, c)
+          && label(b, c) != label(c, d)
+          && label(c, d) != label(d, a)
+      }) {
+        for &[a, _, c, _] in &self.junctions {
+          edge_labels.remove(&UnorderedPair(a, c));
+        }
+        return Some(edge_labels);
+      }
+    }
+    None
+  }
+}
@@ -0,0 +1,63 @@
+use std::fmt::{self, Display};
+
+use crate::*;
+
+/// The errors of the fallible counterparts of operations which panic on bad input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+  /// A division has between 4 and 65535 nodes.
+  NodeCount(usize),
+  TooManyConnections(Node),
+  UnknownNode(Node),
+  NotConnected(Node, Node),
+  MissingLabel(Node, Node),
+  /// A label for two nodes which aren't connected.
+  UnexpectedLabel(Node, Node),
+  /// The labels are of every edge, but don't give each region four sides.
+  InvalidLabelling,
+  /// Walls can't slide or rotate through a four-way junction.
+  FourWayJunction([Node; 4]),
+  Division(DivisionError),
+  Layout(LayoutError),
+  FromLayout(FromLayoutError),
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::NodeCount(count) => write!(f, "{} nodes, not between 4 and 65535", count),
+      Error::TooManyConnections(node) => write!(f, "{:?} has too many connections", node),
+      Error::UnknownNode(node) => write!(f, "unknown node {:?}", node),
+      Error::NotConnected(a, b) => write!(f, "{:?} is not connected to {:?}", a, b),
+      Error::MissingLabel(a, b) => write!(f, "edge {:?}-{:?} is not labeled", a, b),
+      Error::UnexpectedLabel(a, b) => {
+        write!(f, "{:?}-{:?} is labeled, but is not an edge", a, b)
+      }
+      Error::InvalidLabelling => write!(f, "edge labels are not a labelling of the division"),
+      Error::FourWayJunction(junction) => write!(f, "four-way junction {:?}", junction),
+      Error::Division(error) => write!(f, "invalid division: {:?}", error),
+      Error::Layout(error) => write!(f, "invalid layout: {:?}", error),
+      Error::FromLayout(error) => write!(f, "layout not recognised: {:?}", error),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl From<DivisionError> for Error {
+  fn from(error: DivisionError) -> Error {
+    Error::Division(error)
+  }
+}
+
+impl From<LayoutError> for Error {
+  fn from(error: LayoutError) -> Error {
+    Error::Layout(error)
+  }
+}
+
+impl From<FromLayoutError> for Error {
+  fn from(error: FromLayoutError) -> Error {
+    Error::FromLayout(error)
+  }
+}
@@ -0,0 +1,110 @@
+use crate::*;
+
+/// Why a division has no labelling, and so no rectangular layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoLabelling {
+  /// A region needs a connection on each of its four sides.
+  TooFewConnections(Node),
+  /// A region touches two adjacent borders, but isn't the region in their corner.
+  BorderCorner { borders: [Node; 2], region: Node },
+  /// Three connected nodes which don't form a face, so that the nodes inside can't be laid out.
+  SeparatingTriangle([Node; 3]),
+  /// Labels of border edges which contradict each other through the labels they force. Removing
+  /// any one of them removes the contradiction.
+  ContradictingLabels(Vec<(Node, Node, bool)>),
+  /// An edge which can't be labeled either way, with the labels of border edges each label
+  /// contradicts.
+  UnlabelableEdge {
+    edge: UnorderedPair<Node>,
+    if_true: Vec<(Node, Node, bool)>,
+    if_false: Vec<(Node, Node, bool)>,
+  },
+  /// The contradiction is only found after guessing several labels.
+  Other,
+}
+
+/// Finds a reason a division has no labelling, or `None` if it has one.
+pub fn explain_no_labelling(div: &Division) -> Option<NoLabelling> {
+  if label_edges(div).next().is_some() {
+    return None;
+  }
+  if let Some(region) = div.regions().find(|&region| div[region].len() < 4) {
+    return Some(NoLabelling::TooFewConnections(region));
+  }
+  for a in div.nodes() {
+    for b in div[a].iter().filter(|&b| b > a) {
+      for c in div[b].iter().filter(|&c| c > b) {
+        if !div[a].contains_item(c) || is_face(div, a, b, c) {
+          continue;
+        }
+        return Some(if b.is_border() {
+          NoLabelling::BorderCorner {
+            borders: [a, b],
+            region: c,
+          }
+        } else {
+          NoLabelling::SeparatingTriangle([a, b, c])
+        });
+      }
+    }
+  }
+
+  let border_labels = border_labels(div)
+    .into_iter()
+    .filter(|(a, b, _)| !(a.is_border() && b.is_border()))
+    .collect::<Vec<_>>();
+  let forced = match forced_labels(div, border_labels.clone()) {
+    Some(forced) => forced,
+    None => {
+      return Some(NoLabelling::ContradictingLabels(minimise_contradiction(
+        div,
+        border_labels,
+        vec![],
+      )))
+    }
+  };
+  for a in div.nodes() {
+    for b in div[a]
+      .iter()
+      .filter(|&b| b > a && !(a.is_border() && b.is_border()))
+    {
+      let edge = UnorderedPair(a, b);
+      if forced.contains_key(&edge) {
+        continue;
+      }
+      let contradicts = |label| {
+        let mut labels = border_labels.clone();
+        labels.push((a, b, label));
+        forced_labels(div, labels).is_none()
+      };
+      if contradicts(true) && contradicts(false) {
+        return Some(NoLabelling::UnlabelableEdge {
+          edge,
+          if_true: minimise_contradiction(div, border_labels.clone(), vec![(a, b, true)]),
+          if_false: minimise_contradiction(div, border_labels.clone(), vec![(a, b, false)]),
+        });
+      }
+    }
+  }
+  Some(NoLabelling::Other)
+}
+
+/// Removes labels one at a time while they, along with `extra`, still contradict each other.
+fn minimise_contradiction(
+  div: &Division,
+  mut labels: Vec<(Node, Node, bool)>,
+  extra: Vec<(Node, Node, bool)>,
+) -> Vec<(Node, Node, bool)> {
+  let mut i = 0;
+  while i < labels.len() {
+    let mut without = labels.clone();
+    without.remove(i);
+    without.extend(extra.iter().copied());
+    if forced_labels(div, without).is_none() {
+      labels.remove(i);
+    } else {
+      i += 1;
+    }
+  }
+  labels
+}
@@ -0,0 +1,198 @@
+use std::{
+  collections::{HashMap, HashSet, VecDeque},
+  fmt::Write,
+};
+
+use crate::*;
+
+/// The labellings of one division, deduplicated by symmetry as in `generate_svg`, connected when a
+/// single alternating cycle flip turns one into the other.
+pub struct FlipGraph<'a> {
+  pub div: &'a Division,
+  /// One labelling for each vertex.
+  pub edge_labels: Vec<EdgeLabels>,
+  pub hashes: Vec<u64>,
+  pub edges: Vec<(usize, usize)>,
+}
+
+pub fn flip_graph(div: &Division) -> FlipGraph<'_> {
+  let mut graph = FlipGraph {
+    div,
+    edge_labels: vec![],
+    hashes: vec![],
+    edges: vec![],
+  };
+  let mut vertices = HashMap::new();
+  let mut edges = HashSet::new();
+  for edge_labels in edge_label_lattice(div) {
+    let hash = hash_division(div, Some(&edge_labels));
+    let vertex = graph.vertex(&mut vertices, hash, &edge_labels);
+    for cycle in alternating_cycles(div, &edge_labels) {
+      let mut flipped = edge_labels.clone();
+      cycle.flip(&mut flipped);
+      let flipped_hash = hash_division(div, Some(&flipped));
+      let flipped_vertex = graph.vertex(&mut vertices, flipped_hash, &flipped);
+      if vertex != flipped_vertex {
+        edges.insert((vertex.min(flipped_vertex), vertex.max(flipped_vertex)));
+      }
+    }
+  }
+  // Number the vertices by increasing hash, so the graph doesn't depend on the lattice's order.
+  let mut order = (0..graph.hashes.len()).collect::<Vec<_>>();
+  order.sort_unstable_by_key(|&vertex| graph.hashes[vertex]);
+  let mut new_vertices = vec![0; order.len()];
+  for (new_vertex, &vertex) in order.iter().enumerate() {
+    new_vertices[vertex] = new_vertex;
+  }
+  graph.hashes = order.iter().map(|&vertex| graph.hashes[vertex]).collect();
+  graph.edge_labels = order
+    .iter()
+    .map(|&vertex| graph.edge_labels[vertex].clone())
+    .collect();
+  graph.edges = edges
+    .into_iter()
+    .map(|(a, b)| {
+      let (a, b) = (new_vertices[a], new_vertices[b]);
+      (a.min(b), a.max(b))
+    })
+    .collect();
+  graph.edges.sort_unstable();
+  graph
+}
+
+impl FlipGraph<'_> {
+  fn vertex(
+    &mut self,
+    vertices: &mut HashMap<u64, usize>,
+    hash: u64,
+    edge_labels: &EdgeLabels,
+  ) -> usize {
+    *vertices.entry(hash).or_insert_with(|| {
+      self.edge_labels.push(edge_labels.clone());
+      self.hashes.push(hash);
+      self.hashes.len() - 1
+    })
+  }
+
+  /// The vertex of a labelling of the division, or of a labelling symmetric to it.
+  pub fn find(&self, edge_labels: &EdgeLabels) -> Option<usize> {
+    let hash = hash_division(self.div, Some(edge_labels));
+    self.hashes.iter().position(|&x| x == hash)
+  }
+
+  fn neighbours(&self) -> Vec<Vec<usize>> {
+    let mut neighbours = vec![vec![]; self.hashes.len()];
+    for &(a, b) in &self.edges {
+      neighbours[a].push(b);
+      neighbours[b].push(a);
+    }
+    neighbours
+  }
+
+  /// The previous vertex on a shortest path from `from` to each vertex, if it's reachable.
+  fn search(&self, neighbours: &[Vec<usize>], from: usize) -> Vec<Option<(usize, usize)>> {
+    let mut found = vec![None; self.hashes.len()];
+    found[from] = Some((from, 0));
+    let mut queue = VecDeque::from(vec![from]);
+    while let Some(vertex) = queue.pop_front() {
+      let distance = found[vertex].unwrap().1;
+      for &neighbour in &neighbours[vertex] {
+        if found[neighbour].is_none() {
+          found[neighbour] = Some((vertex, distance + 1));
+          queue.push_back(neighbour);
+        }
+      }
+    }
+    found
+  }
+
+  /// The vertices along a shortest sequence of flips from `from` to `to`, including both.
+  pub fn shortest_flip_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+    let found = self.search(&self.neighbours(), from);
+    found[to]?;
+    let mut path = vec![to];
+    while *path.last().unwrap() != from {
+      path.push(found[*path.last().unwrap()].unwrap().0);
+    }
+    path.reverse();
+    Some(path)
+  }
+
+  /// The largest number of flips needed between two labellings, or `None` if some can't be reached
+  /// from each other, which can happen with four-way junctions.
+  pub fn diameter(&self) -> Option<usize> {
+    let neighbours = self.neighbours();
+    let mut diameter = 0;
+    for from in 0..self.hashes.len() {
+      for found in self.search(&neighbours, from) {
+        diameter = diameter.max(found?.1);
+      }
+    }
+    Some(diameter)
+  }
+
+  /// The layout of a labelling, with the division in its canonical orientation, so all of them are
+  /// drawn the same way round.
+  fn layout(
+    &self,
+    edge_labels: &EdgeLabels,
+    layout_fn: fn(&Division, &EdgeLabels) -> Layout,
+  ) -> Layout {
+    let transform = canonical_transform(self.div, None);
+    layout_fn(
+      &self.div.transform(transform),
+      &edge_labels.transform(transform),
+    )
+  }
+
+  pub fn to_dot(&self, layout_fn: fn(&Division, &EdgeLabels) -> Layout) -> String {
+    let mut str = "graph {\n".to_string();
+    for (edge_labels, hash) in self.edge_labels.iter().zip(&self.hashes) {
+      let rects = self
+        .layout(edge_labels, layout_fn)
+        .iter()
+        .map(|rect| format!("{} {} {} {}", rect.x1, rect.y1, rect.x2, rect.y2))
+        .collect::<Vec<_>>()
+        .join(",");
+      writeln!(str, r#"  "{}" [rects="{}"];"#, hash, rects).unwrap();
+    }
+    for &(a, b) in &self.edges {
+      writeln!(str, r#"  "{}" -- "{}";"#, self.hashes[a], self.hashes[b]).unwrap();
+    }
+    str += "}\n";
+    str
+  }
+
+  pub fn to_json(&self, layout_fn: fn(&Division, &EdgeLabels) -> Layout) -> String {
+    // Hashes are written as strings, as they don't fit in a double.
+    let vertices = self
+      .edge_labels
+      .iter()
+      .zip(&self.hashes)
+      .map(|(edge_labels, hash)| {
+        let rects = self
+          .layout(edge_labels, layout_fn)
+          .iter()
+          .map(|rect| format!("[{},{},{},{}]", rect.x1, rect.y1, rect.x2, rect.y2))
+          .collect::<Vec<_>>()
+          .join(",");
+        format!(r#"{{"id":"{}","rects":[{}]}}"#, hash, rects)
+      })
+      .collect::<Vec<_>>()
+      .join(",");
+    let edges = self
+      .edges
+      .iter()
+      .map(|(a, b)| format!("[{},{}]", a, b))
+      .collect::<Vec<_>>()
+      .join(",");
+    format!(
+      r#"{{"vertices":[{}],"edges":[{}],"diameter":{}}}"#,
+      vertices,
+      edges,
+      self
+        .diameter()
+        .map_or_else(|| "null".to_string(), |x| x.to_string()),
+    )
+  }
+}
@@ -0,0 +1,169 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FromLayoutError {
+  Empty,
+  TooManyRegions(usize),
+  NotFinite(Node),
+  NonPositiveArea(Node),
+  Overlap(Node, Node),
+  /// The rects don't overlap, but leave this fraction of their bounding box uncovered.
+  Gap(f64),
+}
+
+const EPSILON: f64 = 1e-9;
+
+/// Recognises the division and edge labels of a list of rects, with rect `i` becoming region `i`.
+/// The rects are scaled to fill the unit square first, and are oriented as in a `Layout`. Rects
+/// which only touch at a corner are not connected, so four rects meeting at a point become a
+/// four-way junction rather than a wall.
+pub fn from_layout(rects: &[Rect]) -> Result<(Division, EdgeLabels), FromLayoutError> {
+  if rects.is_empty() {
+    return Err(FromLayoutError::Empty);
+  }
+  if rects.len() > (u16::MAX - 4) as usize {
+    return Err(FromLayoutError::TooManyRegions(rects.len()));
+  }
+  let regions = (0..rects.len() as u16)
+    .map(Node::region)
+    .collect::<Vec<_>>();
+  for (&region, rect) in regions.iter().zip(rects) {
+    if ![rect.x1, rect.y1, rect.x2, rect.y2]
+      .iter()
+      .all(|x| x.is_finite())
+    {
+      return Err(FromLayoutError::NotFinite(region));
+    }
+  }
+  let min_x = rects
+    .iter()
+    .map(|rect| rect.x1)
+    .fold(f64::INFINITY, f64::min);
+  let min_y = rects
+    .iter()
+    .map(|rect| rect.y1)
+    .fold(f64::INFINITY, f64::min);
+  let max_x = rects
+    .iter()
+    .map(|rect| rect.x2)
+    .fold(f64::NEG_INFINITY, f64::max);
+  let max_y = rects
+    .iter()
+    .map(|rect| rect.y2)
+    .fold(f64::NEG_INFINITY, f64::max);
+  let rects = rects
+    .iter()
+    .map(|rect| Rect {
+      x1: (rect.x1 - min_x) / (max_x - min_x),
+      y1: (rect.y1 - min_y) / (max_y - min_y),
+      x2: (rect.x2 - min_x) / (max_x - min_x),
+      y2: (rect.y2 - min_y) / (max_y - min_y),
+    })
+    .collect::<Vec<_>>();
+  for (&region, rect) in regions.iter().zip(&rects) {
+    if !(rect.width() > EPSILON && rect.height() > EPSILON) {
+      return Err(FromLayoutError::NonPositiveArea(region));
+    }
+  }
+
+  let mut edge_labels = EdgeLabels::new();
+  // The connected nodes along each side of each region, with their position along the side.
+  let mut sides = vec![[vec![], vec![], vec![], vec![]]; rects.len()];
+  for (i, a) in rects.iter().enumerate() {
+    for &(side, touching, position) in &[
+      (0, a.y2 > 1. - EPSILON, 0.),
+      (1, a.x1 < EPSILON, 0.),
+      (2, a.y1 < EPSILON, 0.),
+      (3, a.x2 > 1. - EPSILON, 0.),
+    ] {
+      if touching {
+        sides[i][side].push((position, Node::border(side as u16)));
+        edge_labels.insert(
+          UnorderedPair(regions[i], Node::border(side as u16)),
+          side % 2 == 0,
+        );
+      }
+    }
+    for (j, b) in rects.iter().enumerate().skip(i + 1) {
+      let x_overlap = a.x2.min(b.x2) - a.x1.max(b.x1);
+      let y_overlap = a.y2.min(b.y2) - a.y1.max(b.y1);
+      if x_overlap > EPSILON && y_overlap > EPSILON {
+        return Err(FromLayoutError::Overlap(regions[i], regions[j]));
+      }
+      let (a_side, position) = if x_overlap.abs() < EPSILON && y_overlap > EPSILON {
+        (if a.x2 < b.x2 { 3 } else { 1 }, a.y1.max(b.y1))
+      } else if y_overlap.abs() < EPSILON && x_overlap > EPSILON {
+        (if a.y2 < b.y2 { 0 } else { 2 }, a.x1.max(b.x1))
+      } else {
+        continue;
+      };
+      sides[i][a_side].push((position, regions[j]));
+      sides[j][(a_side + 2) % 4].push((position, regions[i]));
+      edge_labels.insert(UnorderedPair(regions[i], regions[j]), a_side % 2 == 0);
+    }
+  }
+  let uncovered = 1.
+    - rects
+      .iter()
+      .map(|rect| rect.width() * rect.height())
+      .sum::<f64>();
+  if uncovered > EPSILON {
+    return Err(FromLayoutError::Gap(uncovered));
+  }
+
+  // Clockwise, the bottom side runs leftwards, the left upwards, the top rightwards, and the right
+  // downwards.
+  let clockwise = |side: usize, nodes: &mut Vec<(f64, Node)>| {
+    nodes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    if side == 0 || side == 1 {
+      nodes.reverse();
+    }
+  };
+  let mut border_nodes = HashMap::new();
+  for border in 0..4 {
+    let mut nodes = sides
+      .iter()
+      .zip(&rects)
+      .zip(&regions)
+      .filter(|((region_sides, _), _)| {
+        region_sides[border]
+          .iter()
+          .any(|&(_, node)| node == Node::border(border as u16))
+      })
+      .map(|((_, rect), &region)| {
+        let position = if border % 2 == 0 { rect.x1 } else { rect.y1 };
+        (position, region)
+      })
+      .collect::<Vec<_>>();
+    // A border's regions are clockwise around it, i.e. in the opposite direction to the side of
+    // the regions touching it.
+    clockwise(border, &mut nodes);
+    nodes.reverse();
+    border_nodes.insert(border, nodes);
+  }
+  let mut connected_nodes = (0..4u16)
+    .map(|border| {
+      let mut nodes = vec![Node::border(border + 1)];
+      nodes.extend(
+        border_nodes[&(border as usize)]
+          .iter()
+          .map(|&(_, node)| node),
+      );
+      nodes.push(Node::border(border + 3));
+      nodes
+    })
+    .collect::<Vec<_>>();
+  for mut region_sides in sides {
+    let mut nodes = vec![];
+    for (side, side_nodes) in region_sides.iter_mut().enumerate() {
+      clockwise(side, side_nodes);
+      nodes.extend(side_nodes.iter().map(|&(_, node)| node));
+    }
+    connected_nodes.push(nodes);
+  }
+  let div = Division::from_connected_nodes(&connected_nodes).unwrap();
+  debug_assert_eq!(validate_layout(&div, &edge_labels, &rects), Ok(()));
+  Ok((div, edge_labels))
+}
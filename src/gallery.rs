@@ -0,0 +1,119 @@
+use crate::*;
+use chashmap::CHashMap;
+use std::fmt::Write;
+
+/// A standalone HTML page embedding every labelling of every division up to symmetry as an inline
+/// SVG, along with its metadata and controls to filter the entries client-side. A division's
+/// labellings count is the number of entries it has.
+pub fn generate_gallery(
+  divs: CHashMap<u64, Division>,
+  layout_fn: fn(&Division, &EdgeLabels) -> Layout,
+) -> String {
+  let square_size = 100.;
+  let padding = 4.;
+  let mut entries = String::new();
+  let mut count = 0;
+  for (_, div) in sorted_divisions(divs) {
+    let all_edge_labels = sorted_labellings(&div);
+    let degrees = degree_sequence(&div)
+      .iter()
+      .map(|degree| degree.to_string())
+      .collect::<Vec<_>>()
+      .join(" ");
+    for (id, edge_labels) in &all_edge_labels {
+      let symmetry = symmetry_group(&div, Some(edge_labels)).name();
+      let sliceable = is_sliceable(&div, edge_labels);
+      write!(
+        entries,
+        r#"<figure data-id="{}" data-symmetry="{}" data-sliceable="{}" data-labellings="{}" data-degrees="{}">"#,
+        id,
+        symmetry,
+        sliceable,
+        all_edge_labels.len(),
+        degrees,
+      )
+      .unwrap();
+      write!(
+        entries,
+        r#"<svg viewBox="0 0 {0} {0}" xmlns="http://www.w3.org/2000/svg">"#,
+        square_size + 2. * padding,
+      )
+      .unwrap();
+      let transform = canonical_transform(&div, Some(edge_labels));
+      let layout = layout_fn(&div.transform(transform), &edge_labels.transform(transform));
+      write_layout_rects(&mut entries, &layout, padding, padding, square_size);
+      write!(
+        entries,
+        "</svg><figcaption><code>{}</code><br>{} regions, {}, {}<br>{} labelling{}<br>degrees {}</figcaption></figure>",
+        id,
+        div.num_regions(),
+        symmetry,
+        if sliceable { "sliceable" } else { "not sliceable" },
+        all_edge_labels.len(),
+        if all_edge_labels.len() == 1 { "" } else { "s" },
+        degrees,
+      )
+      .unwrap();
+      count += 1;
+    }
+  }
+  let mut symmetry_options = String::new();
+  for group in SymmetryGroup::ALL.iter() {
+    write!(symmetry_options, r#"<option>{}</option>"#, group.name()).unwrap();
+  }
+  format!(
+    r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>rectdissect gallery</title>
+<style>
+body {{ font-family: sans-serif; margin: 1em; }}
+form {{ display: flex; flex-wrap: wrap; gap: 1em; align-items: center; margin-bottom: 1em; }}
+main {{ display: flex; flex-wrap: wrap; gap: 1em; }}
+figure {{ margin: 0; width: 160px; font-size: 0.75em; }}
+figure svg {{ width: 100%; height: auto; }}
+figure[hidden] {{ display: none; }}
+</style>
+</head>
+<body>
+<form id="filters">
+<input type="search" name="search" placeholder="Search ID or degrees">
+<label>Symmetry <select name="symmetry"><option value="">any</option>{symmetry_options}</select></label>
+<label>Sliceable <select name="sliceable"><option value="">any</option><option value="true">yes</option><option value="false">no</option></select></label>
+<label>Labellings <input type="number" name="min_labellings" min="1" placeholder="min" style="width: 5em"> to <input type="number" name="max_labellings" min="1" placeholder="max" style="width: 5em"></label>
+<output id="shown">{count} of {count} shown</output>
+</form>
+<main id="entries">{entries}</main>
+<script>
+const form = document.getElementById("filters");
+const entries = [...document.querySelectorAll("#entries figure")];
+function update() {{
+  const search = form.search.value.trim();
+  const symmetry = form.symmetry.value;
+  const sliceable = form.sliceable.value;
+  const min = form.min_labellings.value === "" ? -Infinity : +form.min_labellings.value;
+  const max = form.max_labellings.value === "" ? Infinity : +form.max_labellings.value;
+  let shown = 0;
+  for (const entry of entries) {{
+    const data = entry.dataset;
+    const labellings = +data.labellings;
+    const visible = (search === "" || data.id.includes(search) || data.degrees.includes(search))
+      && (symmetry === "" || data.symmetry === symmetry)
+      && (sliceable === "" || data.sliceable === sliceable)
+      && labellings >= min && labellings <= max;
+    entry.hidden = !visible;
+    shown += visible;
+  }}
+  document.getElementById("shown").textContent = shown + " of " + entries.length + " shown";
+}}
+form.addEventListener("input", update);
+form.addEventListener("submit", event => event.preventDefault());
+</script>
+</body>
+</html>"##,
+    symmetry_options = symmetry_options,
+    count = count,
+    entries = entries,
+  )
+}
@@ -19,20 +19,36 @@ impl Rect {
   }
 }
 
+/// The rect of each region, in the unit square with `y` increasing downwards. As `generate_layout`
+/// draws them, `b0` is the bottom, `b1` the left, `b2` the top and `b3` the right, so walls
+/// labelled `true` are horizontal.
 pub type Layout = Vec<Rect>;
 
+/// Checks the division and labels before laying them out with `layout_fn`, and the layout after.
+pub fn try_layout(
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  layout_fn: fn(&Division, &EdgeLabels) -> Layout,
+) -> Result<Layout, Error> {
+  div.validate()?;
+  validate_edge_labels(div, edge_labels)?;
+  let layout = layout_fn(div, edge_labels);
+  validate_layout(div, edge_labels, &layout)?;
+  Ok(layout)
+}
+
 pub fn generate_layout(div: &Division, edge_labels: &EdgeLabels) -> Layout {
   let layout_x_0 = generate_1d_layout(div, edge_labels, 0);
   let layout_x_1 = generate_1d_layout(div, edge_labels, 2);
   let layout_y_0 = generate_1d_layout(div, edge_labels, 1);
   let layout_y_1 = generate_1d_layout(div, edge_labels, 3);
 
-  debug_assert_eq!(layout_x_0.len() as u8, div.num_regions() + 1);
-  debug_assert_eq!(layout_x_1.len() as u8, div.num_regions() + 1);
-  debug_assert_eq!(layout_y_0.len() as u8, div.num_regions() + 1);
-  debug_assert_eq!(layout_y_1.len() as u8, div.num_regions() + 1);
+  debug_assert_eq!(layout_x_0.len() as u16, div.num_regions() + 1);
+  debug_assert_eq!(layout_x_1.len() as u16, div.num_regions() + 1);
+  debug_assert_eq!(layout_y_0.len() as u16, div.num_regions() + 1);
+  debug_assert_eq!(layout_y_1.len() as u16, div.num_regions() + 1);
 
-  return div
+  let layout = div
     .regions()
     .map(|region| {
       let [x1_0, x2_0] = layout_x_0[&region];
@@ -49,11 +65,12 @@ pub fn generate_layout(div: &Division, edge_labels: &EdgeLabels) -> Layout {
       }
     })
     .collect();
+  return symmetrise_layout(div, edge_labels, &layout);
 
   fn generate_1d_layout(
     div: &Division,
     edge_labels: &EdgeLabels,
-    root: u8,
+    root: u16,
   ) -> HashMap<Node, [f64; 2]> {
     let axis = root % 2 == 0;
     let mut ranges = HashMap::new();
@@ -122,3 +139,42 @@ pub fn generate_layout(div: &Division, edge_labels: &EdgeLabels) -> Layout {
     ranges
   }
 }
+
+/// Averages the layout over the stabiliser of the dissection, so it is drawn as symmetrically as it
+/// is. The average of layouts of a dissection is still one, as where its walls can go is only
+/// constrained by linear equalities and inequalities.
+pub fn symmetrise_layout(div: &Division, edge_labels: &EdgeLabels, layout: &Layout) -> Layout {
+  let automorphisms = automorphisms(div, Some(edge_labels));
+  if automorphisms.len() == 1 {
+    return layout.clone();
+  }
+  let mut sums = vec![
+    Rect {
+      x1: 0.,
+      y1: 0.,
+      x2: 0.,
+      y2: 0.,
+    };
+    layout.len()
+  ];
+  for automorphism in &automorphisms {
+    // The rect moved from region `r{i}` is where the region it becomes can go.
+    for (i, rect) in layout.transform(automorphism.transform).iter().enumerate() {
+      let sum = &mut sums[(automorphism.regions[i].0 - 4) as usize];
+      sum.x1 += rect.x1;
+      sum.y1 += rect.y1;
+      sum.x2 += rect.x2;
+      sum.y2 += rect.y2;
+    }
+  }
+  let count = automorphisms.len() as f64;
+  sums
+    .iter()
+    .map(|sum| Rect {
+      x1: sum.x1 / count,
+      y1: sum.y1 / count,
+      x2: sum.x2 / count,
+      y2: sum.y2 / count,
+    })
+    .collect()
+}
@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridRect {
+  pub x1: u32,
+  pub y1: u32,
+  pub x2: u32,
+  pub y2: u32,
+}
+
+/// A layout with every wall on an integer coordinate of a `width` by `height` grid. Walls that are
+/// part of the same maximal segment always share a coordinate, so they line up exactly.
+#[derive(Debug, Clone)]
+pub struct GridLayout {
+  pub width: u32,
+  pub height: u32,
+  pub rects: Vec<GridRect>,
+}
+
+impl GridLayout {
+  pub fn to_layout(&self) -> Layout {
+    let width = self.width as f64;
+    let height = self.height as f64;
+    self
+      .rects
+      .iter()
+      .map(|rect| Rect {
+        x1: rect.x1 as f64 / width,
+        y1: rect.y1 as f64 / height,
+        x2: rect.x2 as f64 / width,
+        y2: rect.y2 as f64 / height,
+      })
+      .collect()
+  }
+}
+
+/// Lays out the regions on the smallest grid on which each maximal segment can be placed after
+/// everything that must be before it, found by topologically sorting the segments of each axis.
+pub fn generate_grid_layout(div: &Division, edge_labels: &EdgeLabels) -> GridLayout {
+  let sides = region_sides(div, edge_labels);
+  let x_segments = AxisSegments::new(div, &sides, 1);
+  let y_segments = AxisSegments::new(div, &sides, 0);
+  let xs = x_segments.grid_coordinates();
+  let ys = y_segments.grid_coordinates();
+  let height = ys[y_segments.hi_border];
+  GridLayout {
+    width: xs[x_segments.hi_border],
+    height,
+    rects: x_segments
+      .region_segments
+      .iter()
+      .zip(&y_segments.region_segments)
+      .map(|(&[x1, x2], &[y1, y2])| GridRect {
+        x1: xs[x1],
+        y1: height - ys[y2],
+        x2: xs[x2],
+        y2: height - ys[y1],
+      })
+      .collect(),
+  }
+}
+
+/// The maximal segments perpendicular to one axis, numbered densely. `lo` is the side facing the
+/// low end of the axis: `1` for the x axis, which runs right from `b1`, and `0` for the y axis,
+/// which runs up from `b0`, so is measured from the bottom.
+pub struct AxisSegments {
+  pub count: usize,
+  pub lo_border: usize,
+  pub hi_border: usize,
+  /// The segments on the low and high side of each region.
+  pub region_segments: Vec<[usize; 2]>,
+  /// Pairs of segments where the first must be strictly before the second.
+  pub order: Vec<(usize, usize)>,
+}
+
+impl AxisSegments {
+  pub fn new(div: &Division, sides: &RegionSides, lo: usize) -> AxisSegments {
+    let hi = lo + 2;
+    let regions = div.num_regions() as usize;
+    let lo_border = regions * 2;
+    let hi_border = regions * 2 + 1;
+    let slot = |region: Node, side: usize| (region.0 as usize - 4) * 2 + (side == hi) as usize;
+    let mut parents = (0..regions * 2 + 2).collect::<Vec<_>>();
+    fn find(parents: &mut Vec<usize>, x: usize) -> usize {
+      let parent = parents[x];
+      if parent == x {
+        return x;
+      }
+      let root = find(parents, parent);
+      parents[x] = root;
+      root
+    }
+    fn union(parents: &mut Vec<usize>, x: usize, y: usize) {
+      let (x, y) = (find(parents, x), find(parents, y));
+      parents[x] = y;
+    }
+    for region in div.regions() {
+      for &side in &[lo, hi] {
+        for &node in &sides[&region][side] {
+          let other = if node.is_region() {
+            slot(node, (side + 2) % 4)
+          } else if side == lo {
+            lo_border
+          } else {
+            hi_border
+          };
+          union(&mut parents, slot(region, side), other);
+        }
+      }
+      // Where four regions meet at a point, the walls on both sides of it are the same segment.
+      for side in 0..4 {
+        let next_side = (side + 1) % 4;
+        let (before, after) = (
+          *sides[&region][side].last().unwrap(),
+          sides[&region][next_side][0],
+        );
+        if before.is_border() || after.is_border() || div[before].contains_item(after) {
+          continue;
+        }
+        if side % 2 == lo % 2 {
+          union(&mut parents, slot(region, side), slot(after, side));
+        } else {
+          union(
+            &mut parents,
+            slot(region, next_side),
+            slot(before, next_side),
+          );
+        }
+      }
+    }
+    let mut ids = vec![usize::MAX; parents.len()];
+    let mut count = 0;
+    let mut id = |parents: &mut Vec<usize>, x: usize| {
+      let root = find(parents, x);
+      if ids[root] == usize::MAX {
+        ids[root] = count;
+        count += 1;
+      }
+      ids[root]
+    };
+    let lo_border = id(&mut parents, lo_border);
+    let hi_border = id(&mut parents, hi_border);
+    let region_segments = div
+      .regions()
+      .map(|region| {
+        [
+          id(&mut parents, slot(region, lo)),
+          id(&mut parents, slot(region, hi)),
+        ]
+      })
+      .collect::<Vec<_>>();
+    let mut order = vec![];
+    for region in div.regions() {
+      let [region_lo, region_hi] = region_segments[region.0 as usize - 4];
+      order.push((region_lo, region_hi));
+      // The walls between the neighbours along the perpendicular sides end on this region, so
+      // must be strictly within it. The side after `lo` in clockwise order runs from `lo` to `hi`,
+      // and the side after `hi` runs back.
+      for &(side, increasing) in &[(lo + 1, true), ((lo + 3) % 4, false)] {
+        for pair in sides[&region][side].windows(2) {
+          let lower = if increasing { pair[0] } else { pair[1] };
+          if lower.is_region() {
+            let wall = region_segments[lower.0 as usize - 4][1];
+            order.push((region_lo, wall));
+            order.push((wall, region_hi));
+          }
+        }
+        // Each neighbour across the side must overlap this region, so the wall between them keeps
+        // a positive length, even where the walls ending on it from either side are close.
+        for &neighbour in &sides[&region][side] {
+          if neighbour.is_region() {
+            let [neighbour_lo, neighbour_hi] = region_segments[neighbour.0 as usize - 4];
+            order.push((region_lo, neighbour_hi));
+            order.push((neighbour_lo, region_hi));
+          }
+        }
+      }
+    }
+    AxisSegments {
+      count,
+      lo_border,
+      hi_border,
+      region_segments,
+      order,
+    }
+  }
+
+  /// The smallest integer coordinate of each segment such that every segment is after all the
+  /// segments that must be before it.
+  pub fn grid_coordinates(&self) -> Vec<u32> {
+    let mut coords = vec![0; self.count];
+    let mut in_degrees = vec![0; self.count];
+    let mut successors = vec![vec![]; self.count];
+    for &(from, to) in &self.order {
+      in_degrees[to] += 1;
+      successors[from].push(to);
+    }
+    let mut queue = (0..self.count)
+      .filter(|&segment| in_degrees[segment] == 0)
+      .collect::<VecDeque<_>>();
+    let mut visited = 0;
+    while let Some(segment) = queue.pop_front() {
+      visited += 1;
+      for &next in &successors[segment] {
+        coords[next] = coords[next].max(coords[segment] + 1);
+        in_degrees[next] -= 1;
+        if in_degrees[next] == 0 {
+          queue.push_back(next);
+        }
+      }
+    }
+    debug_assert_eq!(visited, self.count);
+    coords
+  }
+}
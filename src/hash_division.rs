@@ -1,41 +1,127 @@
 use crate::*;
+use chashmap::CHashMap;
 use either::Either;
+use helper_fn::{helper_fn, use_helper_fn};
 use std::{
   collections::hash_map::DefaultHasher,
   hash::{Hash, Hasher},
 };
 
+/// A hash of the division, and of its labelling if given, which is the same for symmetric ones. It
+/// is made with `DefaultHasher`, whose algorithm may change between Rust releases, so hashes, and
+/// the output orders and lineage built on them, are only reproducible with the same toolchain.
 pub fn hash_division(div: &Division, edge_labels: Option<&EdgeLabels>) -> u64 {
-  let mut hash = u64::MAX;
+  hash_division_traversals(div, edge_labels)
+    .iter()
+    .copied()
+    .min()
+    .unwrap()
+}
+
+/// Like `hash_division`, but checks that the division, and the labelling if given, are valid first.
+pub fn try_hash_division(div: &Division, edge_labels: Option<&EdgeLabels>) -> Result<u64, Error> {
+  div.validate()?;
+  if let Some(edge_labels) = edge_labels {
+    validate_edge_labels(div, edge_labels)?;
+  }
+  Ok(hash_division(div, edge_labels))
+}
+
+/// The divisions by increasing hash, which is the order they are output in. Hashes are of the
+/// divisions up to symmetry, so the order doesn't depend on how or on how many threads they were
+/// generated, though it can change with the toolchain.
+pub fn sorted_divisions(divs: CHashMap<u64, Division>) -> Vec<(u64, Division)> {
+  let mut divs = divs.into_iter().collect::<Vec<_>>();
+  divs.sort_unstable_by_key(|&(hash, _)| hash);
+  divs
+}
+
+/// The labellings of a division up to symmetry, by increasing `hash_division`, which is the order
+/// they are output in.
+pub fn sorted_labellings(div: &Division) -> Vec<(u64, EdgeLabels)> {
+  let mut labellings = label_edges(div)
+    .map(|edge_labels| {
+      // Symmetric labellings have the same hash, so the least of them is kept.
+      let mut labels = edge_labels
+        .iter()
+        .map(|(&UnorderedPair(a, b), &label)| (a.min(b), a.max(b), label))
+        .collect::<Vec<_>>();
+      labels.sort_unstable();
+      (hash_division(div, Some(&edge_labels)), labels, edge_labels)
+    })
+    .collect::<Vec<_>>();
+  labellings.sort_unstable_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+  labellings.dedup_by_key(|&mut (hash, _, _)| hash);
+  labellings
+    .into_iter()
+    .map(|(hash, _, edge_labels)| (hash, edge_labels))
+    .collect()
+}
+
+/// The hashes of the eight traversals `hash_division` minimises over, at index
+/// `start * 2 + !dir as usize`.
+pub fn hash_division_traversals(div: &Division, edge_labels: Option<&EdgeLabels>) -> [u64; 8] {
+  let mut hashes = [0; 8];
   for start in 0..4 {
     for dir in [true, false] {
-      let mut hasher = DefaultHasher::new();
-      let mut node_ids = Vec::with_capacity(div.nodes().len());
-      use_helper_fn!(visit_node(
-        div,
-        edge_labels,
-        start,
-        dir,
-        &mut hasher,
-        &mut node_ids,
-      ));
-      visit_node!(
-        Node::border(start),
-        Node::border(start + if dir { 3 } else { 1 }),
-      );
-      let new_hash = hasher.finish();
-      if new_hash < hash {
-        hash = new_hash
-      }
+      hashes[start as usize * 2 + !dir as usize] =
+        traverse_division(div, edge_labels, start, dir).0;
     }
   }
-  return hash;
+  hashes
+}
+
+/// The transform taking a division to the orientation `hash_division` minimises over, in which
+/// equivalent divisions look the same.
+pub fn canonical_transform(div: &Division, edge_labels: Option<&EdgeLabels>) -> Transform {
+  let hashes = hash_division_traversals(div, edge_labels);
+  let i = (0..8).min_by_key(|&i| hashes[i]).unwrap();
+  traversal_transform(i as u16 / 2, i % 2 == 0)
+}
+
+/// The transform after which traversal `(start, dir)` of a division is traversal `(0, true)`.
+/// Traversal `(start, dir)` sees `b{start}` as the bottom border, mirrored left to right unless
+/// `dir`.
+pub fn traversal_transform(start: u16, dir: bool) -> Transform {
+  Transform::from_borders(|k| {
+    if dir {
+      (k + 4 - start) % 4
+    } else {
+      (start + 4 - k) % 4
+    }
+  })
+}
+
+/// The hash of one traversal of the division, starting from `b{start}` and going clockwise if
+/// `dir`, and the nodes in the order they were visited. Traversals with the same hash visit
+/// corresponding nodes in the same order.
+pub fn traverse_division(
+  div: &Division,
+  edge_labels: Option<&EdgeLabels>,
+  start: u16,
+  dir: bool,
+) -> (u64, Vec<Node>) {
+  let mut hasher = DefaultHasher::new();
+  let mut node_ids = Vec::with_capacity(div.nodes().len());
+  use_helper_fn!(visit_node(
+    div,
+    edge_labels,
+    start,
+    dir,
+    &mut hasher,
+    &mut node_ids,
+  ));
+  visit_node!(
+    Node::border(start),
+    Node::border(start + if dir { 3 } else { 1 }),
+  );
+  (hasher.finish(), node_ids)
 }
 
 #[helper_fn(
   div: &Division,
   edge_labels: Option<&EdgeLabels>,
-  start: u8,
+  start: u16,
   dir: bool,
   &mut hasher: DefaultHasher,
   &mut node_ids: Vec<Node>,
@@ -56,7 +142,7 @@ fn visit_node(node: Node, last: Node) {
   }
   if fresh {
     let connected_nodes = &div[node];
-    hasher.write_u8(connected_nodes.len());
+    hasher.write_u16(connected_nodes.len());
     for next in maybe_reverse(connected_nodes.iter_starting_at(last).skip(1), dir) {
       visit_node!(next, node);
     }
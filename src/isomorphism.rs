@@ -0,0 +1,116 @@
+use crate::*;
+
+/// How one dissection maps onto another: by `transform`, with region `r{i}` becoming `regions[i]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Isomorphism {
+  pub transform: Transform,
+  pub regions: Vec<Node>,
+}
+
+impl Isomorphism {
+  /// The node `node` becomes.
+  pub fn node(&self, node: Node) -> Node {
+    if node.is_border() {
+      Node::border(self.transform.border(node.0))
+    } else {
+      self.regions[(node.0 - 4) as usize]
+    }
+  }
+}
+
+/// A way of mapping `a` onto `b`, if they are the same up to symmetry. Without edge labels, only the
+/// divisions are compared. The traversals of `hash_division` with equal hashes give the mapping,
+/// which is then checked, so hash collisions don't give false positives.
+pub fn find_isomorphism(
+  a: &Division,
+  a_edge_labels: Option<&EdgeLabels>,
+  b: &Division,
+  b_edge_labels: Option<&EdgeLabels>,
+) -> Option<Isomorphism> {
+  isomorphisms(a, a_edge_labels, b, b_edge_labels).next()
+}
+
+/// The ways of mapping the dissection onto itself, which is its stabiliser along with where each
+/// symmetry takes the regions, starting with the identity.
+pub fn automorphisms(div: &Division, edge_labels: Option<&EdgeLabels>) -> Vec<Isomorphism> {
+  isomorphisms(div, edge_labels, div, edge_labels).collect()
+}
+
+fn isomorphisms<'a>(
+  a: &'a Division,
+  a_edge_labels: Option<&'a EdgeLabels>,
+  b: &'a Division,
+  b_edge_labels: Option<&'a EdgeLabels>,
+) -> impl Iterator<Item = Isomorphism> + 'a {
+  let comparable =
+    a.num_regions() == b.num_regions() && a_edge_labels.is_some() == b_edge_labels.is_some();
+  let (b_hash, b_nodes) = traverse_division(b, b_edge_labels, 0, true);
+  (0..4)
+    .flat_map(|start| [(start, true), (start, false)])
+    .filter(move |_| comparable)
+    .filter_map(move |(start, dir)| {
+      let (hash, a_nodes) = traverse_division(a, a_edge_labels, start, dir);
+      if hash != b_hash || a_nodes.len() != b_nodes.len() {
+        return None;
+      }
+      let mut regions = vec![Node(0); a.num_regions() as usize];
+      for (a_node, &b_node) in a_nodes.iter().zip(&b_nodes) {
+        if a_node.is_region() {
+          regions[(a_node.0 - 4) as usize] = b_node;
+        }
+      }
+      Some(Isomorphism {
+        transform: traversal_transform(start, dir),
+        regions,
+      })
+    })
+    .filter(move |isomorphism| is_isomorphism(a, a_edge_labels, b, b_edge_labels, isomorphism))
+}
+
+/// A way of mapping layout `a` onto `b`, if they are the same up to symmetry, with rect `i`
+/// becoming region `r{i}`.
+pub fn find_layout_isomorphism(a: &Layout, b: &Layout) -> Result<Option<Isomorphism>, Error> {
+  let (a_div, a_edge_labels) = from_layout(a)?;
+  let (b_div, b_edge_labels) = from_layout(b)?;
+  Ok(find_isomorphism(
+    &a_div,
+    Some(&a_edge_labels),
+    &b_div,
+    Some(&b_edge_labels),
+  ))
+}
+
+fn is_isomorphism(
+  a: &Division,
+  a_edge_labels: Option<&EdgeLabels>,
+  b: &Division,
+  b_edge_labels: Option<&EdgeLabels>,
+  isomorphism: &Isomorphism,
+) -> bool {
+  a.nodes().all(|node| {
+    let mut nodes = a[node]
+      .iter()
+      .map(|x| isomorphism.node(x))
+      .collect::<Vec<_>>();
+    if isomorphism.transform.reflected {
+      nodes.reverse();
+    }
+    let image = isomorphism.node(node);
+    let b_nodes = &b[image];
+    nodes.len() == b_nodes.len() as usize
+      && (nodes.is_empty()
+        || b_nodes.contains_item(nodes[0])
+          && b_nodes.iter_starting_at(nodes[0]).eq(nodes.iter().copied()))
+      && match (a_edge_labels, b_edge_labels) {
+        (Some(a_edge_labels), Some(b_edge_labels)) => a[node].iter().all(|x| {
+          a_edge_labels
+            .get(&UnorderedPair(node, x))
+            .map(|&label| label != isomorphism.transform.swaps_orientation())
+            == b_edge_labels
+              .get(&UnorderedPair(image, isomorphism.node(x)))
+              .copied()
+        }),
+        _ => true,
+      }
+  })
+}
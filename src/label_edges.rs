@@ -21,6 +21,71 @@ pub struct LabelEdgesIter<'a> {
 }
 
 pub fn label_edges(div: &Division) -> LabelEdgesIter<'_> {
+  label_edges_with(div, &EdgeLabels::new())
+}
+
+/// Like `label_edges`, but only yields the labellings which agree with `fixed_labels`. Constraints
+/// which can't be met are usually found before any guessing, in which case nothing is yielded.
+pub fn label_edges_with<'a>(div: &'a Division, fixed_labels: &EdgeLabels) -> LabelEdgesIter<'a> {
+  let mut labels = border_labels(div);
+  labels.extend(
+    fixed_labels
+      .iter()
+      .map(|(&UnorderedPair(a, b), &label)| (a, b, label)),
+  );
+  label_edges_from(div, labels)
+}
+
+/// Like `label_edges`, but checks that the division is valid first.
+pub fn try_label_edges(div: &Division) -> Result<LabelEdgesIter<'_>, Error> {
+  div.validate()?;
+  Ok(label_edges(div))
+}
+
+/// Like indexing the labels, but without panicking if the edge isn't labeled.
+pub fn edge_label(edge_labels: &EdgeLabels, a: Node, b: Node) -> Result<bool, Error> {
+  edge_labels
+    .get(&UnorderedPair(a, b))
+    .copied()
+    .ok_or(Error::MissingLabel(a, b))
+}
+
+/// Checks that the labels are exactly those of one of the labellings of a valid division. The
+/// edges between borders needn't be labeled.
+pub fn validate_edge_labels(div: &Division, edge_labels: &EdgeLabels) -> Result<(), Error> {
+  for &UnorderedPair(a, b) in edge_labels.keys() {
+    if !div.get(a)?.contains_item(b) {
+      return Err(Error::UnexpectedLabel(a, b));
+    }
+  }
+  for a in div.nodes() {
+    for b in div[a].iter().filter(|b| a.is_region() || b.is_region()) {
+      edge_label(edge_labels, a, b)?;
+    }
+  }
+  if label_edges_with(div, edge_labels).next().is_none() {
+    return Err(Error::InvalidLabelling);
+  }
+  Ok(())
+}
+
+/// The labels every labelling gives the edges of the borders.
+pub fn border_labels(div: &Division) -> Vec<(Node, Node, bool)> {
+  let mut labels = Vec::new();
+  for border_n in 0..4 {
+    for node in div[Node::border(border_n)].iter() {
+      labels.push((
+        Node::border(border_n),
+        node,
+        node.is_border() || border_n % 2 == 0,
+      ));
+    }
+  }
+  labels
+}
+
+/// Labels the edges starting from only `labels`, without those of the borders.
+pub fn label_edges_from(div: &Division, labels: Vec<(Node, Node, bool)>) -> LabelEdgesIter<'_> {
   let mut state = State {
     edge_labels: HashMap::new(),
     ambiguous_edges: vec![],
@@ -30,18 +95,13 @@ pub fn label_edges(div: &Division) -> LabelEdgesIter<'_> {
       .flat_map(|a| div[a].iter().map(move |b| UnorderedPair(a, b)))
       .collect(),
   };
-  let mut labels_todo = Vec::new();
+  let nodes = div.num_regions() + 4;
+  let feasible = labels
+    .iter()
+    .all(|&(a, b, _)| a.0 < nodes && b.0 < nodes && div[a].contains_item(b));
+  let mut labels_todo = labels;
   let mut nodes_todo = Vec::new();
-  for border_n in 0..4 {
-    for node in div[Node::border(border_n)].iter() {
-      labels_todo.push((
-        Node::border(border_n),
-        node,
-        node.is_border() || border_n % 2 == 0,
-      ));
-    }
-  }
-  let states = if flush_todos(&mut state, &mut labels_todo, &mut nodes_todo).is_some() {
+  let states = if feasible && flush_todos(&mut state, &mut labels_todo, &mut nodes_todo).is_some() {
     vec![state]
   } else {
     vec![]
@@ -54,6 +114,14 @@ pub fn label_edges(div: &Division) -> LabelEdgesIter<'_> {
   }
 }
 
+/// The labels which follow from `labels` without guessing, or `None` if they contradict each other.
+pub fn forced_labels(div: &Division, labels: Vec<(Node, Node, bool)>) -> Option<EdgeLabels> {
+  label_edges_from(div, labels)
+    .states
+    .pop()
+    .map(|state| state.edge_labels)
+}
+
 impl Iterator for LabelEdgesIter<'_> {
   type Item = EdgeLabels;
   fn next(&mut self) -> Option<EdgeLabels> {
@@ -181,9 +249,9 @@ pub struct ConnectedNodesClassification {
   pub all_false: Vec<Node>,
   pub all_none: Vec<Node>,
   pub vecs: Vec<(Vec<Node>, Option<bool>)>,
-  pub true_vecs_count: u8,
-  pub false_vecs_count: u8,
-  pub none_vecs_count: u8,
+  pub true_vecs_count: u16,
+  pub false_vecs_count: u16,
+  pub none_vecs_count: u16,
 }
 
 pub fn classify_connected_nodes(
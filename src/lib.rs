@@ -0,0 +1,65 @@
+mod area_layout;
+mod aspect_layout;
+mod build_animation;
+mod connected_nodes;
+mod divide;
+mod division;
+mod edge_label_lattice;
+mod error;
+mod explain_no_labelling;
+mod flip_graph;
+mod from_layout;
+mod gallery;
+mod generate_layout;
+mod grid_layout;
+mod hash_division;
+mod isomorphism;
+mod label_edges;
+mod lineage;
+mod mcmc;
+mod node;
+mod properties;
+mod random_generic_rectangulation;
+mod random_mosaic_floorplan;
+mod rank_division;
+mod rectangular_dual;
+mod region_sides;
+mod svg;
+mod transform;
+mod unorderedpair;
+mod validate_division;
+mod validate_layout;
+mod wall_moves;
+
+pub use area_layout::*;
+pub use aspect_layout::*;
+pub use build_animation::*;
+pub use connected_nodes::*;
+pub use divide::*;
+pub use division::*;
+pub use edge_label_lattice::*;
+pub use error::*;
+pub use explain_no_labelling::*;
+pub use flip_graph::*;
+pub use from_layout::*;
+pub use gallery::*;
+pub use generate_layout::*;
+pub use grid_layout::*;
+pub use hash_division::*;
+pub use isomorphism::*;
+pub use label_edges::*;
+pub use lineage::*;
+pub use mcmc::*;
+pub use node::*;
+pub use properties::*;
+pub use random_generic_rectangulation::*;
+pub use random_mosaic_floorplan::*;
+pub use rank_division::*;
+pub use rectangular_dual::*;
+pub use region_sides::*;
+pub use svg::*;
+pub use transform::*;
+pub use unorderedpair::*;
+pub use validate_division::*;
+pub use validate_layout::*;
+pub use wall_moves::*;
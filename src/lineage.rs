@@ -0,0 +1,138 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::*;
+
+/// The generation DAG between levels: which divisions of each level are divided from which of the
+/// level before, and by which cuts. A child with more than one cut or parent is one `add_div` sees
+/// more than once. Cuts are of the parent kept for its hash, so use its region numbers.
+#[derive(Debug, Clone, Default)]
+pub struct Lineage {
+  /// The hashes of the divisions of each level, by increasing hash, starting with the first level
+  /// added.
+  pub levels: Vec<Vec<u64>>,
+  /// The edges from each level to the next, by parent and then child hash.
+  pub edges: Vec<Vec<LineageEdge>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineageEdge {
+  pub parent: u64,
+  pub child: u64,
+  /// In the order `divide` gives them.
+  pub cuts: Vec<Cut>,
+}
+
+impl Lineage {
+  /// Adds the edges from the divisions of the last level, given by the hash of each with those of
+  /// the divisions divided from it which were kept, and the cuts giving them in the order
+  /// `divide_with_cuts` gives them.
+  pub fn add_level(&mut self, children: &[(u64, Vec<(Cut, u64)>)]) {
+    if self.levels.is_empty() {
+      let mut hashes = children.iter().map(|&(hash, _)| hash).collect::<Vec<_>>();
+      hashes.sort_unstable();
+      self.levels.push(hashes);
+    }
+    let mut edges = BTreeMap::new();
+    for (parent, children) in children {
+      for &(cut, child) in children {
+        edges
+          .entry((*parent, child))
+          .or_insert_with(Vec::new)
+          .push(cut);
+      }
+    }
+    let mut hashes = edges.keys().map(|&(_, child)| child).collect::<Vec<_>>();
+    hashes.sort_unstable();
+    hashes.dedup();
+    self.levels.push(hashes);
+    self.edges.push(
+      edges
+        .into_iter()
+        .map(|((parent, child), cuts)| LineageEdge {
+          parent,
+          child,
+          cuts,
+        })
+        .collect(),
+    );
+  }
+
+  pub fn to_dot(&self) -> String {
+    let mut str = "digraph {\n".to_string();
+    for (i, hashes) in self.levels.iter().enumerate() {
+      write!(str, "  {{ rank=same;").unwrap();
+      for hash in hashes {
+        write!(str, r#" "{}" [level={}];"#, hash, i).unwrap();
+      }
+      str += " }\n";
+    }
+    for edge in self.edges.iter().flatten() {
+      let cuts = edge
+        .cuts
+        .iter()
+        .map(|cut| {
+          format!(
+            "{:?} {:?} {:?} {} {}",
+            cut.region, cut.from, cut.to, cut.share_from, cut.share_to
+          )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+      writeln!(
+        str,
+        r#"  "{}" -> "{}" [cuts="{}"];"#,
+        edge.parent, edge.child, cuts
+      )
+      .unwrap();
+    }
+    str += "}\n";
+    str
+  }
+
+  pub fn to_json(&self) -> String {
+    // Hashes are written as strings, as they don't fit in a double.
+    let levels = self
+      .levels
+      .iter()
+      .map(|hashes| {
+        let hashes = hashes
+          .iter()
+          .map(|hash| format!(r#""{}""#, hash))
+          .collect::<Vec<_>>()
+          .join(",");
+        format!("[{}]", hashes)
+      })
+      .collect::<Vec<_>>()
+      .join(",");
+    let edges = self
+      .edges
+      .iter()
+      .map(|edges| {
+        let edges = edges
+          .iter()
+          .map(|edge| {
+            let cuts = edge
+              .cuts
+              .iter()
+              .map(|cut| {
+                format!(
+                  r#"{{"region":"{:?}","from":"{:?}","to":"{:?}","share_from":{},"share_to":{}}}"#,
+                  cut.region, cut.from, cut.to, cut.share_from, cut.share_to
+                )
+              })
+              .collect::<Vec<_>>()
+              .join(",");
+            format!(
+              r#"{{"parent":"{}","child":"{}","cuts":[{}]}}"#,
+              edge.parent, edge.child, cuts
+            )
+          })
+          .collect::<Vec<_>>()
+          .join(",");
+        format!("[{}]", edges)
+      })
+      .collect::<Vec<_>>()
+      .join(",");
+    format!(r#"{{"levels":[{}],"edges":[{}]}}"#, levels, edges)
+  }
+}
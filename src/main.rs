@@ -1,39 +1,32 @@
-mod connected_nodes;
-mod divide;
-mod division;
-mod generate_layout;
-mod hash_division;
-mod label_edges;
-mod node;
-mod svg;
-mod unorderedpair;
-
-pub(crate) use connected_nodes::*;
-pub(crate) use divide::*;
-pub(crate) use division::*;
-pub(crate) use generate_layout::*;
-pub(crate) use hash_division::*;
-pub(crate) use label_edges::*;
-pub(crate) use node::*;
-pub(crate) use svg::*;
-pub(crate) use unorderedpair::*;
+use rectdissect::*;
 
 use chashmap::CHashMap;
 use helper_fn::{helper_fn, use_helper_fn};
-use rayon::iter::{ParallelBridge, ParallelIterator};
-use std::{fmt::Debug, time::Instant};
+use rayon::iter::{IntoParallelRefIterator, ParallelBridge, ParallelIterator};
+use std::time::Instant;
 
 fn main() {
   let oeis_mode = false;
+  let gallery_mode = false;
+  let grid_layout = false;
+  let equal_area_layout = false;
+  let aspect_layout = false;
+  let validate_layouts = false;
+  let flip_graph_mode = false;
+  let symmetric_mode = false;
+  let lineage_mode = false;
+  let animation_mode = false;
+  let explain_dropped = false;
 
   let start = Instant::now();
 
   let mut divs = CHashMap::new();
   let oeis_count = CHashMap::new();
+  let mut lineage = Lineage::default();
 
   use_helper_fn! {
     print_state(&divs, oeis_mode, &oeis_count),
-    add_div(&divs, oeis_mode, &oeis_count),
+    add_div(&divs, oeis_mode, &oeis_count, explain_dropped),
   }
 
   add_div!(Division::default());
@@ -42,15 +35,137 @@ fn main() {
   for i in 2..=5 {
     oeis_count.clear();
     let round_start = Instant::now();
+    let parents = std::mem::replace(&mut divs, CHashMap::new());
+    if lineage_mode {
+      let children = parents
+        .into_iter()
+        .collect::<Vec<_>>()
+        .par_iter()
+        .map(|(hash, div)| {
+          let children = divide_with_cuts(div)
+            .filter_map(|(cut, child)| Some((cut, add_div!(child)?)))
+            .collect();
+          (*hash, children)
+        })
+        .collect::<Vec<_>>();
+      lineage.add_level(&children);
+    } else {
+      parents
+        .into_iter()
+        .flat_map(|(_, div)| iter_with_owned(div, divide))
+        .par_bridge()
+        .for_each(|div| {
+          add_div!(div);
+        });
+    }
+    print_state!(i, start, round_start);
+  }
+
+  let layout_fn: fn(&Division, &EdgeLabels) -> Layout = if grid_layout {
+    |div, edge_labels| generate_grid_layout(div, edge_labels).to_layout()
+  } else if equal_area_layout {
+    |div, edge_labels| {
+      let areas = vec![1.; div.num_regions() as usize];
+      generate_area_layout(div, edge_labels, &areas).unwrap_or_else(|error| {
+        eprintln!(
+          "no equal-area layout: {:?}\n{:?}\n{:?}",
+          error, div, edge_labels
+        );
+        generate_layout(div, edge_labels)
+      })
+    }
+  } else if aspect_layout {
+    |div, edge_labels| match generate_aspect_layout(
+      div,
+      edge_labels,
+      AspectObjective::MinMaxAspectRatio,
+      None,
+    ) {
+      Ok(aspect_layout) => aspect_layout.layout,
+      Err(error) => {
+        eprintln!(
+          "no aspect layout: {:?}\n{:?}\n{:?}",
+          error, div, edge_labels
+        );
+        generate_layout(div, edge_labels)
+      }
+    }
+  } else {
+    generate_layout
+  };
+
+  if validate_layouts {
+    let validated = CHashMap::new();
     std::mem::replace(&mut divs, CHashMap::new())
       .into_iter()
-      .flat_map(|(_, div)| iter_with_owned(div, divide))
       .par_bridge()
-      .for_each(|div| add_div!(div));
-    print_state!(i, start, round_start);
+      .for_each(|(hash, div)| {
+        if let Err(error) = div.validate() {
+          eprintln!("invalid division: {:?}\n{:?}", error, div);
+        }
+        for edge_labels in label_edges(&div) {
+          match try_layout(&div, &edge_labels, layout_fn) {
+            Err(error) => eprintln!("{}\n{:?}\n{:?}", error, div, edge_labels),
+            Ok(layout) => {
+              if let Ok((layout_div, layout_edge_labels)) = from_layout(&layout) {
+                if hash_division(&layout_div, Some(&layout_edge_labels))
+                  != hash_division(&div, Some(&edge_labels))
+                {
+                  eprintln!("layout not recognised: {:?}\n{:?}", div, edge_labels);
+                }
+              }
+            }
+          }
+        }
+        if four_way_junctions(&div).is_empty() {
+          let edges = div
+            .regions()
+            .flat_map(|a| div[a].iter().map(move |b| (a, b)))
+            .filter(|&(a, b)| b.is_region() && a < b)
+            .map(|(a, b)| ((a.0 - 4) as usize, (b.0 - 4) as usize))
+            .collect::<Vec<_>>();
+          if let Err(error) = rectangular_dual(div.num_regions() as usize, &edges, layout_fn) {
+            eprintln!("no rectangular dual: {:?}\n{:?}", error, div);
+          }
+        }
+        validated.insert(hash, div);
+      });
+    divs = validated;
   }
 
-  println!("{}", generate_svg(divs, oeis_mode, oeis_count));
+  if lineage_mode {
+    println!("{}", lineage.to_json());
+  } else if flip_graph_mode {
+    let flip_graphs = sorted_divisions(divs)
+      .into_iter()
+      .map(|(_, div)| flip_graph(&div).to_json(layout_fn))
+      .collect::<Vec<_>>();
+    println!("[{}]", flip_graphs.join(","));
+  } else if animation_mode {
+    // One animated SVG per line, of the first labelling of each division.
+    for (_, div) in sorted_divisions(divs) {
+      let (_, edge_labels) = &sorted_labellings(&div)[0];
+      match build_sequence(&div, edge_labels, layout_fn) {
+        Ok(sequence) => println!("{}", sequence.to_animated_svg(1.)),
+        Err(error) => eprintln!("no build sequence: {:?}\n{:?}", error, div),
+      }
+    }
+  } else if symmetric_mode {
+    let dissections = symmetric_dissections(divs, oeis_mode);
+    for group in &SymmetryGroup::ALL[1..] {
+      let count = dissections
+        .iter()
+        .flat_map(|(_, labellings)| labellings)
+        .filter(|(labelled_group, _, _)| labelled_group == group)
+        .count();
+      eprintln!("{:<15}{}", group.name(), count);
+    }
+    println!("{}", generate_symmetric_svg(&dissections, layout_fn));
+  } else if gallery_mode {
+    println!("{}", generate_gallery(divs, layout_fn));
+  } else {
+    println!("{}", generate_svg(divs, oeis_mode, oeis_count, layout_fn));
+  }
 
   #[helper_fn(
     &divs: CHashMap<u64, Division>,
@@ -73,27 +188,44 @@ fn main() {
     );
   }
 
+  // Returns the hash of `div`, unless it was dropped for having no labelling.
   #[helper_fn(
     &divs: CHashMap<u64, Division>,
     oeis_mode: bool,
     &oeis_count: CHashMap<u64, ()>,
+    explain_dropped: bool,
   )]
-  fn add_div(div: Division) {
+  fn add_div(div: Division) -> Option<u64> {
     let hash = hash_division(&div, None);
-    if !divs.contains_key(&hash) {
+    // Of the symmetric divisions generated, keep the least, so the next level is generated from
+    // the same ones whatever order they're found in.
+    if let Some(mut old) = divs.get_mut(&hash) {
+      if div < *old {
+        *old = div;
+      }
+    } else {
       let mut any = false;
-      for edge_labels in label_edges(&div) {
-        any = true;
-        if oeis_mode {
+      if oeis_mode {
+        for edge_labels in edge_label_lattice(&div) {
+          any = true;
           oeis_count.insert(hash_division(&div, Some(&edge_labels)), ());
-        } else {
-          break;
         }
+      } else {
+        any = label_edges(&div).next().is_some();
       }
       if any {
-        divs.insert(hash, div);
+        divs.alter(hash, |old| match old {
+          Some(old) if old < div => Some(old),
+          _ => Some(div),
+        });
+      } else {
+        if explain_dropped {
+          eprintln!("dropped: {:?}\n{:?}", explain_no_labelling(&div), div);
+        }
+        return None;
       }
     }
+    Some(hash)
   }
 }
 
@@ -0,0 +1,208 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+use crate::*;
+
+type Energy = dyn Fn(&Division, &EdgeLabels) -> f64;
+
+/// A Markov chain over the rectangulations with a fixed number of rooms and no four-way junctions,
+/// moving by sliding and rotating walls.
+///
+/// Each step picks an edge uniformly, and then either one of its `wall_flips`, or if its rooms
+/// share a whole wall, rotating it either way, ending on a uniformly chosen room or border beyond
+/// each of them. The proposal is accepted by Metropolis-Hastings, with the stationary distribution
+/// uniform, or proportional to `exp(-energy)`.
+pub struct MarkovChain {
+  div: Division,
+  edge_labels: EdgeLabels,
+  energy: Box<Energy>,
+  current_energy: f64,
+  rng: Pcg64,
+  steps: usize,
+  accepted: usize,
+}
+
+pub fn markov_chain(
+  div: Division,
+  edge_labels: EdgeLabels,
+  seed: u64,
+) -> Result<MarkovChain, Error> {
+  div.validate()?;
+  validate_edge_labels(&div, &edge_labels)?;
+  if let Some(&junction) = four_way_junctions(&div).first() {
+    return Err(Error::FourWayJunction(junction));
+  }
+  Ok(MarkovChain {
+    div,
+    edge_labels,
+    energy: Box::new(|_, _| 0.),
+    current_energy: 0.,
+    rng: Pcg64::seed_from_u64(seed),
+    steps: 0,
+    accepted: 0,
+  })
+}
+
+impl MarkovChain {
+  /// Targets the distribution proportional to `exp(-energy)` instead of the uniform one.
+  pub fn with_energy(mut self, energy: impl Fn(&Division, &EdgeLabels) -> f64 + 'static) -> Self {
+    self.current_energy = energy(&self.div, &self.edge_labels);
+    self.energy = Box::new(energy);
+    self
+  }
+
+  pub fn div(&self) -> &Division {
+    &self.div
+  }
+
+  pub fn edge_labels(&self) -> &EdgeLabels {
+    &self.edge_labels
+  }
+
+  pub fn energy(&self) -> f64 {
+    self.current_energy
+  }
+
+  /// The fraction of proposals accepted so far, or `None` before the first step.
+  pub fn acceptance_rate(&self) -> Option<f64> {
+    (self.steps > 0).then(|| self.accepted as f64 / self.steps as f64)
+  }
+
+  /// Takes one step, returning whether the proposal was accepted.
+  pub fn step(&mut self) -> bool {
+    self.steps += 1;
+    let edges = self
+      .div
+      .nodes()
+      .flat_map(|a| self.div[a].iter().map(move |b| (a, b)))
+      .collect::<Vec<_>>();
+    let (a, b) = edges[self.rng.gen_range(0..edges.len())];
+    let (proposal, ratio) = if self.rng.gen() {
+      let mut flips = wall_flips(&self.div, &self.edge_labels, a, b);
+      if flips.is_empty() {
+        return false;
+      }
+      let count = flips.len();
+      let proposal = flips.swap_remove(self.rng.gen_range(0..count));
+      let (c, d) = (
+        self.div[a].get_item_after(b),
+        self.div[a].get_item_before(b),
+      );
+      let ratio = count as f64 / wall_flips(&proposal.0, &proposal.1, c, d).len() as f64;
+      (proposal, ratio)
+    } else if let Some(sides) = wall_sides(&self.div, &self.edge_labels, a, b) {
+      let ends = [
+        sides.beyond_a[self.rng.gen_range(0..sides.beyond_a.len())],
+        sides.beyond_b[self.rng.gen_range(0..sides.beyond_b.len())],
+      ];
+      let clockwise = self.rng.gen();
+      let proposal = rotate_wall(&self.div, &self.edge_labels, a, b, clockwise, ends).unwrap();
+      // Rotating back picks the ends of the wall from the sides which were across from it.
+      let ratio = (sides.beyond_a.len() * sides.beyond_b.len()) as f64
+        / (sides.after_a.len() * sides.after_b.len()) as f64;
+      (proposal, ratio)
+    } else {
+      return false;
+    };
+    let energy = (self.energy)(&proposal.0, &proposal.1);
+    let acceptance = ratio * (self.current_energy - energy).exp();
+    if acceptance < 1. && self.rng.gen::<f64>() >= acceptance {
+      return false;
+    }
+    self.div = proposal.0;
+    self.edge_labels = proposal.1;
+    self.current_energy = energy;
+    self.accepted += 1;
+    true
+  }
+
+  /// Takes `samples * thin` steps, recording `observable` every `thin` steps.
+  pub fn run(
+    &mut self,
+    samples: usize,
+    thin: usize,
+    mut observable: impl FnMut(&Division, &EdgeLabels) -> f64,
+  ) -> Vec<f64> {
+    (0..samples)
+      .map(|_| {
+        for _ in 0..thin {
+          self.step();
+        }
+        observable(&self.div, &self.edge_labels)
+      })
+      .collect()
+  }
+
+  pub fn diagnostics(&self, samples: &[f64]) -> MixingDiagnostics {
+    let mut diagnostics = mixing_diagnostics(samples);
+    diagnostics.acceptance_rate = self.acceptance_rate();
+    diagnostics
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixingDiagnostics {
+  pub acceptance_rate: Option<f64>,
+  pub mean: f64,
+  pub variance: f64,
+  /// The integrated autocorrelation time, in samples.
+  pub autocorrelation_time: f64,
+  pub effective_sample_size: f64,
+}
+
+/// Diagnostics of a series of samples of an observable, without the acceptance rate. The
+/// autocorrelation time is summed up to the first lag at least 5 times the sum so far.
+pub fn mixing_diagnostics(samples: &[f64]) -> MixingDiagnostics {
+  let n = samples.len();
+  let mean = samples.iter().sum::<f64>() / n as f64;
+  let autocovariance = |lag: usize| {
+    (0..n - lag)
+      .map(|i| (samples[i] - mean) * (samples[i + lag] - mean))
+      .sum::<f64>()
+      / n as f64
+  };
+  let variance = autocovariance(0);
+  let mut autocorrelation_time = 1.;
+  if variance > 0. {
+    for lag in 1..n {
+      autocorrelation_time += 2. * autocovariance(lag) / variance;
+      if lag as f64 >= 5. * autocorrelation_time {
+        break;
+      }
+    }
+  }
+  let autocorrelation_time = autocorrelation_time.max(1.);
+  MixingDiagnostics {
+    acceptance_rate: None,
+    mean,
+    variance,
+    autocorrelation_time,
+    effective_sample_size: n as f64 / autocorrelation_time,
+  }
+}
+
+/// The Gelman-Rubin potential scale reduction of chains of equal length, which approaches 1 as
+/// they converge to the same distribution. It needs at least two chains of at least two samples.
+pub fn potential_scale_reduction(chains: &[Vec<f64>]) -> Option<f64> {
+  let n = chains.first()?.len();
+  if chains.len() < 2 || n < 2 || chains.iter().any(|chain| chain.len() != n) {
+    return None;
+  }
+  let m = chains.len() as f64;
+  let n = n as f64;
+  let means = chains
+    .iter()
+    .map(|chain| chain.iter().sum::<f64>() / n)
+    .collect::<Vec<_>>();
+  let mean = means.iter().sum::<f64>() / m;
+  let between = n / (m - 1.) * means.iter().map(|x| (x - mean).powi(2)).sum::<f64>();
+  let within = chains
+    .iter()
+    .zip(&means)
+    .map(|(chain, chain_mean)| {
+      chain.iter().map(|x| (x - chain_mean).powi(2)).sum::<f64>() / (n - 1.)
+    })
+    .sum::<f64>()
+    / m;
+  Some((((n - 1.) / n * within + between / n) / within).sqrt())
+}
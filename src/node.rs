@@ -1,13 +1,13 @@
 use std::fmt::Debug;
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Node(pub u8);
+pub struct Node(pub u16);
 
 impl Node {
-  pub const fn border(n: u8) -> Node {
+  pub const fn border(n: u16) -> Node {
     Node(n % 4)
   }
-  pub const fn region(n: u8) -> Node {
+  pub const fn region(n: u16) -> Node {
     Node(n + 4)
   }
   pub const fn is_border(&self) -> bool {
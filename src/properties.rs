@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use chashmap::CHashMap;
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymmetryGroup {
+  Trivial,
+  /// Only the half turn.
+  C2,
+  /// The quarter turns.
+  C4,
+  /// A single mirror through the midpoints of two sides.
+  D1Axis,
+  /// A single mirror along a diagonal.
+  D1Diagonal,
+  /// Both axis mirrors and the half turn.
+  D2Axes,
+  /// Both diagonal mirrors and the half turn.
+  D2Diagonals,
+  D4,
+}
+
+impl SymmetryGroup {
+  pub const ALL: [SymmetryGroup; 8] = [
+    SymmetryGroup::Trivial,
+    SymmetryGroup::C2,
+    SymmetryGroup::C4,
+    SymmetryGroup::D1Axis,
+    SymmetryGroup::D1Diagonal,
+    SymmetryGroup::D2Axes,
+    SymmetryGroup::D2Diagonals,
+    SymmetryGroup::D4,
+  ];
+  pub fn name(&self) -> &'static str {
+    match self {
+      SymmetryGroup::Trivial => "C1",
+      SymmetryGroup::C2 => "C2",
+      SymmetryGroup::C4 => "C4",
+      SymmetryGroup::D1Axis => "D1 (axis)",
+      SymmetryGroup::D1Diagonal => "D1 (diagonal)",
+      SymmetryGroup::D2Axes => "D2 (axes)",
+      SymmetryGroup::D2Diagonals => "D2 (diagonals)",
+      SymmetryGroup::D4 => "D4",
+    }
+  }
+}
+
+/// Finds the stabiliser of the dissection by comparing the hashes of all eight traversals of
+/// `hash_division`, so it is exact up to hash collisions.
+pub fn symmetry_group(div: &Division, edge_labels: Option<&EdgeLabels>) -> SymmetryGroup {
+  let hashes = hash_division_traversals(div, edge_labels);
+  // Traversal `start * 2 + !dir as usize` views `b{start}` as the bottom border; the odd ones are
+  // mirrored.
+  let fixed = |i: usize| hashes[i] == hashes[0];
+  let quarter_turn = fixed(2) || fixed(6);
+  let half_turn = fixed(4);
+  let axis_mirror = fixed(1) || fixed(5);
+  let diagonal_mirror = fixed(3) || fixed(7);
+  match (quarter_turn, half_turn, axis_mirror, diagonal_mirror) {
+    (true, _, true, _) | (true, _, _, true) => SymmetryGroup::D4,
+    (true, _, false, false) => SymmetryGroup::C4,
+    (false, true, true, _) => SymmetryGroup::D2Axes,
+    (false, true, false, true) => SymmetryGroup::D2Diagonals,
+    (false, true, false, false) => SymmetryGroup::C2,
+    (false, false, true, _) => SymmetryGroup::D1Axis,
+    (false, false, false, true) => SymmetryGroup::D1Diagonal,
+    (false, false, false, false) => SymmetryGroup::Trivial,
+  }
+}
+
+/// A division with the labellings of it to draw, each with its symmetry group and hash.
+pub type SymmetricDissection = (Division, Vec<(SymmetryGroup, u64, EdgeLabels)>);
+
+/// The dissections with a nontrivial stabiliser, in output order, each with the symmetry of its
+/// labelling. In `oeis_mode` each labelling is a dissection; otherwise each division is, drawn with
+/// its first labelling with the same symmetry if it has one, or else its first symmetric labelling.
+/// Divisions with no symmetric labelling are left out.
+///
+/// A symmetric dissection needn't be divided from a symmetric one, so they can't be generated from
+/// each other level by level, and are picked out of the whole level instead.
+pub fn symmetric_dissections(
+  divs: CHashMap<u64, Division>,
+  oeis_mode: bool,
+) -> Vec<SymmetricDissection> {
+  sorted_divisions(divs)
+    .into_iter()
+    .filter_map(|(_, div)| {
+      let group = symmetry_group(&div, None);
+      if group == SymmetryGroup::Trivial {
+        return None;
+      }
+      let mut labellings = sorted_labellings(&div)
+        .into_iter()
+        .map(|(hash, edge_labels)| (symmetry_group(&div, Some(&edge_labels)), hash, edge_labels))
+        .collect::<Vec<_>>();
+      labellings.retain(|&(group, _, _)| group != SymmetryGroup::Trivial);
+      if !oeis_mode && !labellings.is_empty() {
+        let i = labellings
+          .iter()
+          .position(|&(labelled_group, _, _)| labelled_group == group)
+          .unwrap_or(0);
+        labellings = vec![labellings.swap_remove(i)];
+      }
+      (!labellings.is_empty()).then_some((div, labellings))
+    })
+    .collect()
+}
+
+/// Whether the dissection can be produced by recursively cutting rectangles in two with straight
+/// lines (i.e. is a guillotine/slicing floorplan).
+pub fn is_sliceable(div: &Division, edge_labels: &EdgeLabels) -> bool {
+  let sides = region_sides(div, edge_labels);
+  return sliceable(&sides, div.regions().collect());
+
+  fn sliceable(sides: &RegionSides, regions: HashSet<Node>) -> bool {
+    if regions.len() == 1 {
+      return true;
+    }
+    // A horizontal cut exists iff the regions reachable from a region on the bottom edge without
+    // stepping upwards are not everything, and likewise for vertical cuts.
+    for (start_side, closed_sides) in [(0, [0, 1, 3]), (1, [1, 0, 2])] {
+      let seed = *regions
+        .iter()
+        .find(|region| {
+          !sides[region][start_side]
+            .iter()
+            .any(|x| regions.contains(x))
+        })
+        .unwrap();
+      let mut part = HashSet::new();
+      let mut todo = vec![seed];
+      while let Some(region) = todo.pop() {
+        if !part.insert(region) {
+          continue;
+        }
+        for &side in &closed_sides {
+          todo.extend(
+            sides[&region][side]
+              .iter()
+              .filter(|x| regions.contains(x) && !part.contains(x)),
+          );
+        }
+      }
+      if part.len() < regions.len() {
+        let rest = regions.difference(&part).copied().collect();
+        return sliceable(sides, part) && sliceable(sides, rest);
+      }
+    }
+    false
+  }
+}
+
+/// The number of connected nodes (including borders) of each region, largest first.
+pub fn degree_sequence(div: &Division) -> Vec<u16> {
+  let mut degrees = div
+    .regions()
+    .map(|region| div[region].len())
+    .collect::<Vec<_>>();
+  degrees.sort_unstable_by(|a, b| b.cmp(a));
+  degrees
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+use crate::*;
+
+/// Samples generic rectangulations, i.e. divisions with edge labels but no four-way junctions,
+/// exactly uniformly. Each is built by inserting rooms in the top left corner, covering the first
+/// `j` rooms along the left or the top. The wall the covered rooms end on has some walls ending on
+/// it from the other side, and the new room's far side is placed after any number of those that
+/// are nearer the corner than the covered rooms' far sides.
+///
+/// The choices are weighted by the number of rectangulations each leads to, which is counted from
+/// the number of such walls for every room along the left and the top. Unlike the labels of
+/// `random_mosaic_floorplans`, these have no bounded summary, so the counts are memoised over whole
+/// states and their number grows exponentially with `regions`: this is only practical up to about
+/// 20 regions. Beyond that, sample `random_mosaic_floorplans` and run a `markov_chain` from it,
+/// which is not exactly uniform.
+pub struct RandomGenericRectangulations {
+  regions: usize,
+  counts: HashMap<(Corner, usize), u128>,
+}
+
+pub fn random_generic_rectangulations(regions: usize) -> RandomGenericRectangulations {
+  assert!(regions >= 1 && regions <= (u16::MAX - 4) as usize);
+  RandomGenericRectangulations {
+    regions,
+    counts: HashMap::new(),
+  }
+}
+
+/// A reproducible sample of `random_generic_rectangulations(regions)`.
+pub fn random_generic_rectangulation(regions: usize, seed: u64) -> (Division, EdgeLabels) {
+  random_generic_rectangulations(regions).sample(&mut Pcg64::seed_from_u64(seed))
+}
+
+/// For each room along the left and along the top, from the corner, the number of walls ending on
+/// the far side of it, between the corner and the rooms before it.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Corner([Vec<u16>; 2]);
+
+impl Corner {
+  /// Each insertion, as the side it is along, the number of rooms it covers, and the number of
+  /// walls it is placed after, with the resulting state.
+  fn children(&self) -> impl Iterator<Item = (usize, usize, usize, Corner)> + '_ {
+    (0..2).flat_map(move |along| {
+      let across = 1 - along;
+      let walls = &self.0[along];
+      (1..=walls.len()).flat_map(move |covered| {
+        (0..=walls[covered - 1] as usize).map(move |after| {
+          let mut child = self.clone();
+          child.0[along].splice(..covered, std::iter::once(after as u16));
+          child.0[across].insert(0, covered as u16 - 1);
+          (along, covered, after, child)
+        })
+      })
+    })
+  }
+}
+
+impl RandomGenericRectangulations {
+  /// The number of rectangulations with `remaining` more rooms inserted into `corner`.
+  fn count(&mut self, corner: &Corner, remaining: usize) -> u128 {
+    if remaining == 0 {
+      return 1;
+    }
+    if let Some(&count) = self.counts.get(&(corner.clone(), remaining)) {
+      return count;
+    }
+    let count = corner
+      .children()
+      .map(|(_, _, _, child)| self.count(&child, remaining - 1))
+      .sum();
+    self.counts.insert((corner.clone(), remaining), count);
+    count
+  }
+
+  /// The number of generic rectangulations with `regions` regions.
+  pub fn total(&mut self) -> u128 {
+    self.count(&Corner([vec![0], vec![0]]), self.regions - 1)
+  }
+
+  pub fn sample<R: Rng + ?Sized>(&mut self, rng: &mut R) -> (Division, EdgeLabels) {
+    let mut corner = Corner([vec![0], vec![0]]);
+    let mut rectangulation = Rectangulation::new();
+    for remaining in (0..self.regions - 1).rev() {
+      let children = corner.children().collect::<Vec<_>>();
+      let weights = children
+        .iter()
+        .map(|(_, _, _, child)| self.count(child, remaining))
+        .collect::<Vec<_>>();
+      let mut x = rng.gen_range(0..weights.iter().sum::<u128>());
+      let choice = weights
+        .iter()
+        .position(|&weight| {
+          if x < weight {
+            return true;
+          }
+          x -= weight;
+          false
+        })
+        .unwrap();
+      let (along, covered, after, child) = children.into_iter().nth(choice).unwrap();
+      rectangulation.insert(along, covered, after);
+      corner = child;
+    }
+    rectangulation.build()
+  }
+}
+
+/// Rooms with sides given by coordinate ids, along with the order of the ids on each axis.
+struct Rectangulation {
+  /// The ids of each room's left, top, right and bottom sides. On each axis, the low border is id
+  /// 0 and the high border is id 1.
+  rooms: Vec<[usize; 4]>,
+  orders: [Vec<usize>; 2],
+  /// The rooms along the left and along the top, from the corner.
+  corner: [Vec<usize>; 2],
+}
+
+impl Rectangulation {
+  fn new() -> Rectangulation {
+    Rectangulation {
+      rooms: vec![[0, 0, 1, 1]],
+      orders: [vec![0, 1], vec![0, 1]],
+      corner: [vec![0], vec![0]],
+    }
+  }
+
+  fn rank(&self, axis: usize, id: usize) -> usize {
+    self.orders[axis].iter().position(|&x| x == id).unwrap()
+  }
+
+  /// Inserts a room in the top left corner, covering the first `covered` rooms along the top if
+  /// `along` is 1, or along the left if it's 0, with its far side after `after` of the walls ending
+  /// on the far side of the last covered room.
+  fn insert(&mut self, along: usize, covered: usize, after: usize) {
+    let across = 1 - along;
+    let room = self.rooms.len();
+    let covered_rooms = self.corner[along].drain(..covered).collect::<Vec<_>>();
+    let end = self.rooms[covered_rooms[covered - 1]][across + 2];
+    let limit = covered_rooms
+      .iter()
+      .map(|&covered_room| self.rank(along, self.rooms[covered_room][along + 2]))
+      .min()
+      .unwrap();
+    let mut walls = self
+      .rooms
+      .iter()
+      .filter(|sides| sides[across] == end)
+      .map(|sides| self.rank(along, sides[along + 2]))
+      .filter(|&rank| rank < limit)
+      .collect::<Vec<_>>();
+    walls.sort_unstable();
+    let id = self.orders[along].len();
+    // As far from the corner as it can go, so it stays after every wall that was counted on the
+    // far sides of the rooms after the covered ones.
+    let rank = walls.get(after).copied().unwrap_or(limit);
+    self.orders[along].insert(rank, id);
+    for &covered_room in &covered_rooms {
+      self.rooms[covered_room][along] = id;
+    }
+    let mut sides = [0; 4];
+    sides[along + 2] = id;
+    sides[across + 2] = end;
+    self.rooms.push(sides);
+    self.corner[along].insert(0, room);
+    self.corner[across].insert(0, room);
+  }
+
+  fn build(&self) -> (Division, EdgeLabels) {
+    let coords = |axis: usize| {
+      let order = &self.orders[axis];
+      let mut coords = vec![0.; order.len()];
+      for (rank, &id) in order.iter().enumerate() {
+        coords[id] = rank as f64;
+      }
+      coords
+    };
+    let (xs, ys) = (coords(0), coords(1));
+    let rects = self
+      .rooms
+      .iter()
+      .map(|&[x1, y1, x2, y2]| Rect {
+        x1: xs[x1],
+        y1: ys[y1],
+        x2: xs[x2],
+        y2: ys[y2],
+      })
+      .collect::<Vec<_>>();
+    from_layout(&rects).unwrap()
+  }
+}
@@ -0,0 +1,258 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+use crate::*;
+
+/// Samples rectangulations exactly uniformly up to sliding walls along each other, i.e. uniformly
+/// among mosaic floorplans, of which there are a Baxter number. Each is built by inserting rooms in
+/// the bottom left corner, either left of the first few rooms along the left or below the first few
+/// rooms along the bottom, and is returned as its diagonal rectangulation, where every room touches
+/// the diagonal from the bottom left to the top right.
+///
+/// This is not uniform among labellings, as only the diagonal rectangulation of each floorplan is
+/// ever returned: with 4 rooms, there are 22 floorplans but 24 labellings without a four-way
+/// junction. For those, use `random_generic_rectangulations` while it is small enough, or run a
+/// `markov_chain` from a sample.
+///
+/// The choices are weighted by the number of floorplans each leads to. With `h` rooms along the
+/// left and `k` along the bottom, these are counted in layers by the number of rooms still to
+/// insert, each layer scaled to a maximum of 1. Only every `block`th layer is kept, and the rest are recounted
+/// while sampling.
+pub struct RandomMosaicFloorplans {
+  regions: usize,
+  block: usize,
+  checkpoints: Vec<Layer>,
+}
+
+pub fn random_mosaic_floorplans(regions: usize) -> RandomMosaicFloorplans {
+  assert!(regions >= 1 && regions <= (u16::MAX - 4) as usize);
+  let block = (regions as f64).sqrt().ceil() as usize;
+  let mut checkpoints = vec![];
+  let mut layer = Layer {
+    limit: regions + 1,
+    counts: vec![1.; (regions + 1) * regions / 2],
+  };
+  for m in 0..regions {
+    if m % block == 0 {
+      checkpoints.push(layer.clone());
+    }
+    layer = layer.next();
+  }
+  RandomMosaicFloorplans {
+    regions,
+    block,
+    checkpoints,
+  }
+}
+
+/// A reproducible sample of `random_mosaic_floorplans(regions)`.
+pub fn random_mosaic_floorplan(regions: usize, seed: u64) -> (Division, EdgeLabels) {
+  random_mosaic_floorplans(regions).sample(&mut Pcg64::seed_from_u64(seed))
+}
+
+/// The scaled counts of the labels `(h, k)` with `h + k <= limit`, by `k` and then `h`.
+#[derive(Clone)]
+struct Layer {
+  limit: usize,
+  counts: Vec<f64>,
+}
+
+impl Layer {
+  fn index(&self, h: usize, k: usize) -> usize {
+    (k - 1) * self.limit - (k - 1) * k / 2 + h - 1
+  }
+
+  /// Counts with one more room to insert. A label's children are `(i, k + 1)` for `i <= h` and
+  /// `(h + 1, j)` for `j <= k`.
+  fn next(&self) -> Layer {
+    let limit = self.limit - 1;
+    let mut next = Layer {
+      limit,
+      counts: vec![0.; limit * (limit - 1) / 2],
+    };
+    for k in 1..limit {
+      let mut sum = 0.;
+      for h in 1..=limit - k {
+        sum += self.counts[self.index(h, k + 1)];
+        let i = next.index(h, k);
+        next.counts[i] = sum;
+      }
+    }
+    for h in 1..limit {
+      let mut sum = 0.;
+      for k in 1..=limit - h {
+        sum += self.counts[self.index(h + 1, k)];
+        let i = next.index(h, k);
+        next.counts[i] += sum;
+      }
+    }
+    let max = next.counts.iter().copied().fold(0., f64::max);
+    for count in &mut next.counts {
+      *count /= max;
+    }
+    next
+  }
+}
+
+impl RandomMosaicFloorplans {
+  pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> (Division, EdgeLabels) {
+    self.samples(1, rng).pop().unwrap()
+  }
+
+  /// Independent samples, sharing the work of recounting.
+  pub fn samples<R: Rng + ?Sized>(&self, count: usize, rng: &mut R) -> Vec<(Division, EdgeLabels)> {
+    let mut floorplans = (0..count).map(|_| Floorplan::new()).collect::<Vec<_>>();
+    let mut layers = vec![];
+    for m in (1..self.regions).rev() {
+      let c = (m - 1) / self.block;
+      if (m - 1) % self.block == self.block - 1 || layers.is_empty() {
+        layers = vec![self.checkpoints[c].clone()];
+        for _ in c * self.block + 1..m {
+          let layer = layers[layers.len() - 1].next();
+          layers.push(layer);
+        }
+      }
+      let layer = &layers[m - 1 - c * self.block];
+      for floorplan in &mut floorplans {
+        let (h, k) = (
+          floorplan.segments[1].after.len(),
+          floorplan.segments[0].after.len(),
+        );
+        let weights = (1..=h)
+          .map(|i| layer.counts[layer.index(h - i + 1, k + 1)])
+          .chain((1..=k).map(|j| layer.counts[layer.index(h + 1, k - j + 1)]))
+          .collect::<Vec<_>>();
+        let mut x = rng.gen::<f64>() * weights.iter().sum::<f64>();
+        let choice = weights
+          .iter()
+          .position(|&weight| {
+            x -= weight;
+            x < 0.
+          })
+          .unwrap_or(weights.len() - 1);
+        if choice < h {
+          floorplan.insert(1, choice + 1);
+        } else {
+          floorplan.insert(0, choice - h + 1);
+        }
+      }
+    }
+    floorplans.iter().map(Floorplan::build).collect()
+  }
+}
+
+/// A wall, with the rooms on either side of it, from left to right or from bottom to top.
+#[derive(Default)]
+struct Segment {
+  before: Vec<usize>,
+  after: Vec<usize>,
+}
+
+struct Floorplan {
+  /// The borders are segments 0 to 3.
+  segments: Vec<Segment>,
+  /// The segments of each room's bottom, left, top and right sides.
+  rooms: Vec<[usize; 4]>,
+}
+
+impl Floorplan {
+  fn new() -> Floorplan {
+    let mut segments = (0..4).map(|_| Segment::default()).collect::<Vec<_>>();
+    segments[0].after.push(0);
+    segments[1].after.push(0);
+    segments[2].before.push(0);
+    segments[3].before.push(0);
+    Floorplan {
+      segments,
+      rooms: vec![[0, 1, 2, 3]],
+    }
+  }
+
+  /// Inserts a room in the bottom left corner, covering the first `covered` rooms along the left if
+  /// `along` is 1, or along the bottom if it's 0.
+  fn insert(&mut self, along: usize, covered: usize) {
+    let across = 1 - along;
+    let room = self.rooms.len();
+    let segment = self.segments.len();
+    let covered_rooms = self.segments[along]
+      .after
+      .drain(..covered)
+      .collect::<Vec<_>>();
+    self.segments[along].after.insert(0, room);
+    self.segments[across].after.insert(0, room);
+    let end = self.rooms[covered_rooms[covered - 1]][across + 2];
+    self.segments[end].before.insert(0, room);
+    for &covered_room in &covered_rooms {
+      self.rooms[covered_room][along] = segment;
+    }
+    self.segments.push(Segment {
+      before: vec![room],
+      after: covered_rooms,
+    });
+    let mut sides = [0, 1, 0, 0];
+    sides[along + 2] = segment;
+    sides[across + 2] = end;
+    self.rooms.push(sides);
+  }
+
+  /// Builds the diagonal rectangulation of the floorplan. Along each wall, every room on the bottom
+  /// or left side of it meets the first room on the other side, and the last room meets every room
+  /// on the other side.
+  fn build(&self) -> (Division, EdgeLabels) {
+    let segments = &self.segments;
+    let region = |room: usize| Node::region(room as u16);
+    let mut edge_labels = EdgeLabels::new();
+    let mut connected_nodes = (0..4)
+      .map(|border| vec![Node::border(border + 1)])
+      .collect::<Vec<_>>();
+    connected_nodes[0].extend(segments[0].after.iter().copied().map(region));
+    connected_nodes[1].extend(segments[1].after.iter().rev().copied().map(region));
+    connected_nodes[2].extend(segments[2].before.iter().rev().copied().map(region));
+    connected_nodes[3].extend(segments[3].before.iter().copied().map(region));
+    for (border, nodes) in connected_nodes.iter_mut().enumerate() {
+      for &node in &nodes[1..] {
+        edge_labels.insert(
+          UnorderedPair(Node::border(border as u16), node),
+          border % 2 == 0,
+        );
+      }
+      nodes.push(Node::border(border as u16 + 3));
+    }
+
+    for (room, sides) in self.rooms.iter().enumerate() {
+      let mut nodes = vec![];
+      for (side, &segment) in sides.iter().enumerate() {
+        if segment < 4 {
+          nodes.push(Node::border(segment as u16));
+          continue;
+        }
+        let Segment { before, after } = &segments[segment];
+        // The rooms met on this side, from left to right or from bottom to top.
+        let met = if side < 2 {
+          if after[0] == room {
+            &before[..]
+          } else {
+            &before[before.len() - 1..]
+          }
+        } else if before[before.len() - 1] == room {
+          &after[..]
+        } else {
+          &after[..1]
+        };
+        let start = nodes.len();
+        nodes.extend(met.iter().map(|&room| region(room)));
+        // Going clockwise, the bottom and right sides are met backwards.
+        if side == 0 || side == 3 {
+          nodes[start..].reverse();
+        }
+        for &node in &nodes[start..] {
+          edge_labels.insert(UnorderedPair(region(room), node), side % 2 == 0);
+        }
+      }
+      connected_nodes.push(nodes);
+    }
+    let div = Division::from_connected_nodes(&connected_nodes).unwrap();
+    debug_assert_eq!(div.validate(), Ok(()));
+    (div, edge_labels)
+  }
+}
@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+/// Numbers the divisions with a given number of regions, up to symmetry and with at least one
+/// labelling, from 0. Each division's parent in the generation tree is the one with the smallest
+/// `hash_division` of those it can be divided from, and the divisions are in the order of a depth
+/// first traversal visiting children by increasing hash. So two divisions are ordered by the hashes
+/// of their ancestors with 2 regions, then those with 3 regions, and so on, and finally by their own
+/// hashes.
+///
+/// The number of divisions below each division in the tree is counted as needed and kept, so
+/// ranking and unranking only visit the parts of the level before the division.
+pub struct DivisionRanking {
+  regions: u16,
+  counts: HashMap<u64, u64>,
+}
+
+pub fn division_ranking(regions: u16) -> DivisionRanking {
+  assert!(regions >= 1);
+  DivisionRanking {
+    regions,
+    counts: HashMap::new(),
+  }
+}
+
+/// The division with one region fewer that `div` is generated from, if it has more than one.
+pub fn canonical_parent(div: &Division) -> Option<Division> {
+  let mut parents = div
+    .regions()
+    .flat_map(|a| {
+      div[a]
+        .iter()
+        .filter(move |&b| b.is_region() && b > a)
+        .map(move |b| (a, b))
+    })
+    .map(|(a, b)| merge_regions(div, a, b))
+    .filter(|parent| parent.validate().is_ok())
+    .map(|parent| (hash_division(&parent, None), parent))
+    .collect::<Vec<_>>();
+  // Finding a labelling is the slow part, so only look until one has one.
+  parents.sort_unstable_by_key(|&(hash, _)| hash);
+  parents
+    .into_iter()
+    .map(|(_, parent)| parent)
+    .find(|parent| label_edges(parent).next().is_some())
+}
+
+/// The divisions generated from `div`, by increasing hash.
+pub fn canonical_children(div: &Division) -> Vec<Division> {
+  let hash = hash_division(div, None);
+  let mut seen = HashSet::new();
+  let mut children = vec![];
+  for child in divide(div) {
+    let child_hash = hash_division(&child, None);
+    if seen.insert(child_hash)
+      && label_edges(&child).next().is_some()
+      && canonical_parent(&child).map(|parent| hash_division(&parent, None)) == Some(hash)
+    {
+      children.push((child_hash, child));
+    }
+  }
+  children.sort_unstable_by_key(|&(hash, _)| hash);
+  children.into_iter().map(|(_, child)| child).collect()
+}
+
+/// Merges two neighbouring regions into `a`, removing `b`.
+fn merge_regions(div: &Division, a: Node, b: Node) -> Division {
+  let mut merged = div[a].iter_starting_at(b).skip(1).collect::<Vec<_>>();
+  let mut b_nodes = div[b].iter_starting_at(a).skip(1).collect::<Vec<_>>();
+  // The nodes on triangular faces with `a` and `b` are connected to both.
+  if b_nodes.last() == merged.first() {
+    b_nodes.pop();
+  }
+  if b_nodes.first() == merged.last() {
+    b_nodes.remove(0);
+  }
+  merged.extend(b_nodes);
+  let renumber = |node: Node| match node {
+    node if node == b => a,
+    node if node > b => Node(node.0 - 1),
+    node => node,
+  };
+  let connected_nodes = div
+    .nodes()
+    .filter(|&node| node != b)
+    .map(|node| {
+      let nodes = if node == a {
+        merged.clone()
+      } else {
+        div[node].iter().collect()
+      };
+      let mut nodes = nodes.into_iter().map(renumber).collect::<Vec<_>>();
+      nodes.dedup();
+      if nodes.len() > 1 && nodes.first() == nodes.last() {
+        nodes.pop();
+      }
+      nodes
+    })
+    .collect::<Vec<_>>();
+  Division::from_connected_nodes(&connected_nodes).unwrap()
+}
+
+impl DivisionRanking {
+  /// The number of divisions with `self.regions` regions below `div`, which has `regions`.
+  fn count_below(&mut self, div: &Division, regions: u16) -> u64 {
+    if regions == self.regions {
+      return 1;
+    }
+    let hash = hash_division(div, None);
+    if let Some(&count) = self.counts.get(&hash) {
+      return count;
+    }
+    let count = canonical_children(div)
+      .iter()
+      .map(|child| self.count_below(child, regions + 1))
+      .sum();
+    self.counts.insert(hash, count);
+    count
+  }
+
+  pub fn count(&mut self) -> u64 {
+    self.count_below(&Division::default(), 1)
+  }
+
+  /// The rank of `div`, if it has `self.regions` regions and a labelling.
+  pub fn rank(&mut self, div: &Division) -> Option<u64> {
+    if div.num_regions() != self.regions || label_edges(div).next().is_none() {
+      return None;
+    }
+    let mut ancestors = vec![];
+    let mut ancestor = canonical_parent(div);
+    while let Some(div) = ancestor {
+      ancestor = canonical_parent(&div);
+      ancestors.push(div);
+    }
+    if ancestors.len() + 1 != self.regions as usize {
+      return None;
+    }
+    ancestors.reverse();
+    let mut rank = 0;
+    for (i, parent) in ancestors.iter().enumerate() {
+      let hash = hash_division(ancestors.get(i + 1).unwrap_or(div), None);
+      for child in canonical_children(parent) {
+        if hash_division(&child, None) == hash {
+          break;
+        }
+        rank += self.count_below(&child, i as u16 + 2);
+      }
+    }
+    Some(rank)
+  }
+
+  /// The division with the given rank, if there are more than `rank`.
+  pub fn unrank(&mut self, mut rank: u64) -> Option<Division> {
+    if rank >= self.count() {
+      return None;
+    }
+    let mut div = Division::default();
+    for regions in 2..=self.regions {
+      let mut children = canonical_children(&div).into_iter();
+      div = loop {
+        let child = children.next()?;
+        let count = self.count_below(&child, regions);
+        if rank < count {
+          break child;
+        }
+        rank -= count;
+      };
+    }
+    Some(div)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+
+  #[test]
+  fn unrank_inverts_rank() {
+    for regions in 1..=5 {
+      let mut ranking = division_ranking(regions);
+      let count = ranking.count();
+      for rank in 0..count {
+        let div = ranking.unrank(rank).unwrap();
+        assert_eq!(ranking.rank(&div), Some(rank), "{} regions", regions);
+      }
+      assert!(ranking.unrank(count).is_none(), "{} regions", regions);
+    }
+  }
+}
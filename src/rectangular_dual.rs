@@ -0,0 +1,374 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DualError {
+  NoRooms,
+  TooManyRooms(usize),
+  RoomOutOfRange(usize),
+  SelfLoop(usize),
+  Disconnected,
+  /// Three rooms which are all adjacent, but don't form a face of the graph, so the rooms inside
+  /// can't be laid out.
+  SeparatingTriangle([usize; 3]),
+  /// The rooms adjacent to this room don't form a wheel, a fan, or two fans if it splits the
+  /// others in two, so the graph isn't internally triangulated.
+  NotTriangulated(usize),
+  NotPlanar,
+  /// No assignment of the borders to the outside rooms has a labelling.
+  NoLabelling,
+}
+
+/// A planar embedding of a room adjacency graph, as the clockwise order of the rooms around each
+/// room, with `None` where the room meets the outside.
+#[derive(Debug, Clone)]
+pub struct Embedding {
+  pub rotations: Vec<Vec<Option<usize>>>,
+  /// The rooms met going clockwise around the outside, with the index of the `None` in their
+  /// rotation where they're met. A room splitting the others in two is met twice.
+  pub outside: Vec<(usize, usize)>,
+}
+
+/// Finds an embedding of an internally triangulated planar graph without separating triangles.
+/// This is unique up to reflection, except that the rooms on either side of a room splitting the
+/// others in two can be reflected separately.
+pub fn embed_graph(rooms: usize, edges: &[(usize, usize)]) -> Result<Embedding, DualError> {
+  if rooms == 0 {
+    return Err(DualError::NoRooms);
+  }
+  if rooms > (u16::MAX - 4) as usize {
+    return Err(DualError::TooManyRooms(rooms));
+  }
+  let mut adjacent = vec![HashSet::new(); rooms];
+  for &(a, b) in edges {
+    if let Some(&room) = [a, b].iter().find(|&&room| room >= rooms) {
+      return Err(DualError::RoomOutOfRange(room));
+    }
+    if a == b {
+      return Err(DualError::SelfLoop(a));
+    }
+    adjacent[a].insert(b);
+    adjacent[b].insert(a);
+  }
+  if !connected(&adjacent, &[]) {
+    return Err(DualError::Disconnected);
+  }
+  if rooms == 1 {
+    return Ok(Embedding {
+      rotations: vec![vec![None]],
+      outside: vec![(0, 0)],
+    });
+  }
+
+  // Without separating triangles, the rooms adjacent to both a room and one of its neighbours are
+  // exactly those sharing a face with them, so the faces around each room link its neighbours into
+  // a cycle, or into paths between the places it meets the outside.
+  let mut rotations = vec![];
+  for room in 0..rooms {
+    let link = |a: usize| {
+      adjacent[room]
+        .iter()
+        .copied()
+        .filter(|b| adjacent[a].contains(b))
+        .collect::<Vec<_>>()
+    };
+    for &a in &adjacent[room] {
+      // An edge is only on two faces, so if it's on more triangles, those between the outermost
+      // two separate the rooms on either side of them.
+      let common = link(a);
+      if common.len() > 2 {
+        for &c in &common {
+          let others = common.iter().filter(|&&x| x != c).collect::<Vec<_>>();
+          let mut reached = vec![false; rooms];
+          reach(&adjacent, &[room, a, c], *others[0], &mut reached);
+          if others.iter().any(|&&x| !reached[x]) {
+            return Err(DualError::SeparatingTriangle([room, a, c]));
+          }
+        }
+      }
+    }
+    let ends = adjacent[room]
+      .iter()
+      .copied()
+      .filter(|&a| link(a).len() < 2)
+      .collect::<Vec<_>>();
+    let mut rotation = vec![];
+    let mut walked = HashSet::new();
+    let mut walks = 0;
+    for start in ends.iter().copied().chain(adjacent[room].iter().copied()) {
+      if walked.contains(&start) {
+        continue;
+      }
+      walks += 1;
+      let mut next = Some(start);
+      while let Some(a) = next {
+        walked.insert(a);
+        rotation.push(Some(a));
+        next = link(a).into_iter().find(|b| !walked.contains(b));
+      }
+      if !ends.is_empty() {
+        rotation.push(None);
+      }
+    }
+    // Two paths mean the room meets the outside twice, which is only possible if it splits the
+    // others in two.
+    let paths = rotation.iter().filter(|a| a.is_none()).count();
+    if (ends.is_empty() && walks != 1) || paths > 2 || (paths == 2 && connected(&adjacent, &[room]))
+    {
+      return Err(DualError::NotTriangulated(room));
+    }
+    rotations.push(rotation);
+  }
+  if rotations.iter().all(|rotation| !rotation.contains(&None)) {
+    // Every face is a triangle, so whichever is on the outside separates the rest.
+    let rotation = &rotations[0];
+    return Err(DualError::SeparatingTriangle([
+      0,
+      rotation[0].unwrap(),
+      rotation[1].unwrap(),
+    ]));
+  }
+
+  // Orient the rotations consistently, so that each face is clockwise around all its rooms.
+  let after = |rotations: &Vec<Vec<Option<usize>>>, room: usize, a: usize| {
+    let rotation = &rotations[room];
+    let i = rotation.iter().position(|&x| x == Some(a)).unwrap();
+    rotation[(i + 1) % rotation.len()]
+  };
+  let mut oriented = vec![false; rooms];
+  oriented[0] = true;
+  let mut todo = VecDeque::from(vec![0]);
+  while let Some(room) = todo.pop_front() {
+    for a in rotations[room].clone().into_iter().flatten() {
+      let b = adjacent[room].iter().find(|b| adjacent[a].contains(b));
+      let consistent = match b {
+        Some(&b) => {
+          (after(&rotations, room, a) == Some(b)) == (after(&rotations, a, b) == Some(room))
+        }
+        None => true,
+      };
+      if oriented[a] {
+        if !consistent {
+          return Err(DualError::NotPlanar);
+        }
+        continue;
+      }
+      if !consistent {
+        let rotation = &mut rotations[a];
+        rotation.reverse();
+        if rotation[0].is_none() {
+          rotation.rotate_left(1);
+        }
+      }
+      oriented[a] = true;
+      todo.push_back(a);
+    }
+  }
+
+  let edge_count = adjacent.iter().map(HashSet::len).sum::<usize>() / 2;
+  let face_count = (0..rooms)
+    .flat_map(|room| adjacent[room].iter().map(move |&a| (room, a)))
+    .filter(|&(room, a)| after(&rotations, room, a).is_some())
+    .count()
+    / 3
+    + 1;
+  if rooms + face_count != edge_count + 2 {
+    return Err(DualError::NotPlanar);
+  }
+
+  // Going clockwise around the outside, a room is left towards the room after the gap it was
+  // arrived at.
+  let start_room = (0..rooms)
+    .find(|&room| rotations[room].contains(&None))
+    .unwrap();
+  let start = (
+    start_room,
+    rotations[start_room]
+      .iter()
+      .position(Option::is_none)
+      .unwrap(),
+  );
+  let mut outside = vec![start];
+  loop {
+    let (room, gap) = *outside.last().unwrap();
+    let rotation = &rotations[room];
+    let next = rotation[(gap + 1) % rotation.len()].unwrap();
+    let i = rotations[next]
+      .iter()
+      .position(|&x| x == Some(room))
+      .unwrap();
+    let next_gap = (i + 1) % rotations[next].len();
+    if rotations[next][next_gap].is_some() {
+      return Err(DualError::NotPlanar);
+    }
+    if (next, next_gap) == start {
+      break;
+    }
+    outside.push((next, next_gap));
+  }
+  let gaps = rotations
+    .iter()
+    .map(|rotation| rotation.iter().filter(|a| a.is_none()).count())
+    .sum::<usize>();
+  if outside.len() != gaps {
+    return Err(DualError::NotPlanar);
+  }
+  if outside.len() == 3 && rooms > 3 {
+    return Err(DualError::SeparatingTriangle([
+      outside[0].0,
+      outside[1].0,
+      outside[2].0,
+    ]));
+  }
+
+  Ok(Embedding { rotations, outside })
+}
+
+/// Whether the graph is still connected after removing `removed`.
+fn connected(adjacent: &[HashSet<usize>], removed: &[usize]) -> bool {
+  let start = match (0..adjacent.len()).find(|room| !removed.contains(room)) {
+    Some(start) => start,
+    None => return true,
+  };
+  let mut reached = vec![false; adjacent.len()];
+  reach(adjacent, removed, start, &mut reached);
+  (0..adjacent.len()).all(|room| reached[room] || removed.contains(&room))
+}
+
+/// Marks the rooms reachable from `start` without passing through `removed`.
+fn reach(adjacent: &[HashSet<usize>], removed: &[usize], start: usize, reached: &mut [bool]) {
+  reached[start] = true;
+  let mut todo = vec![start];
+  while let Some(room) = todo.pop() {
+    for &a in &adjacent[room] {
+      if !reached[a] && !removed.contains(&a) {
+        reached[a] = true;
+        todo.push(a);
+      }
+    }
+  }
+}
+
+impl Embedding {
+  /// The ways to split the outside into the four borders, as the positions along the outside of
+  /// the bottom right, bottom left, top left and top right corner rooms, where `b0` to `b3` start.
+  /// Positions past the end wrap around.
+  pub fn corner_assignments(&self) -> impl Iterator<Item = [usize; 4]> {
+    let len = self.outside.len();
+    let max = len.max(2) - 1;
+    (0..len).flat_map(move |c0| {
+      (0..=max).flat_map(move |l0| {
+        (0..=max).flat_map(move |l1| {
+          (0..=max).filter_map(move |l2| {
+            let l3 = len.checked_sub(l0 + l1 + l2)?;
+            if l3 > max || (len == 1 && l0 + l1 + l2 != 0) {
+              return None;
+            }
+            Some([c0, c0 + l0, c0 + l0 + l1, c0 + l0 + l1 + l2])
+          })
+        })
+      })
+    })
+  }
+
+  /// The division with room `i` as region `i`, and the borders attached according to `corners`.
+  pub fn division(&self, corners: [usize; 4]) -> Division {
+    let len = self.outside.len();
+    let ends = [corners[1], corners[2], corners[3], corners[0] + len];
+    let mut border_rooms = vec![];
+    let mut visit_borders = vec![vec![]; len];
+    for border in 0..4 {
+      let positions = if len == 1 {
+        0..=0
+      } else {
+        corners[border]..=ends[border]
+      };
+      let mut along = vec![];
+      for i in positions {
+        visit_borders[i % len].push(border as u16);
+        along.push(self.outside[i % len].0);
+      }
+      border_rooms.push(along);
+    }
+    let mut connected_nodes = (0..4)
+      .map(|border| {
+        let mut nodes = vec![Node::border(border as u16 + 1)];
+        nodes.extend(
+          border_rooms[border]
+            .iter()
+            .rev()
+            .map(|&room| Node::region(room as u16)),
+        );
+        nodes.push(Node::border(border as u16 + 3));
+        nodes
+      })
+      .collect::<Vec<_>>();
+    for (room, rotation) in self.rotations.iter().enumerate() {
+      let mut nodes = vec![];
+      for (gap, &a) in rotation.iter().enumerate() {
+        if let Some(a) = a {
+          nodes.push(Node::region(a as u16));
+          continue;
+        }
+        // The borders at a gap are those the outside passes, starting from the one it arrives
+        // along.
+        let visit = self.outside.iter().position(|&x| x == (room, gap)).unwrap();
+        let borders = &visit_borders[visit];
+        let first = (0..4)
+          .find(|&border| borders.contains(&border) && !borders.contains(&((border + 3) % 4)))
+          .unwrap_or(0);
+        nodes.extend(
+          (first..first + 4)
+            .filter(|border| borders.contains(&(border % 4)))
+            .map(Node::border),
+        );
+      }
+      connected_nodes.push(nodes);
+    }
+    Division::from_connected_nodes(&connected_nodes).unwrap()
+  }
+}
+
+pub struct CornerAssignment {
+  pub corners: [usize; 4],
+  pub div: Division,
+  /// Why the division has no labelling, or `None` if it has one.
+  pub no_labelling: Option<NoLabelling>,
+}
+
+impl Embedding {
+  /// The divisions from every corner assignment, leaving out those equivalent by symmetry to an
+  /// earlier one.
+  pub fn all_corner_assignments(&self) -> Vec<CornerAssignment> {
+    let mut hashes = HashSet::new();
+    self
+      .corner_assignments()
+      .map(|corners| (corners, self.division(corners)))
+      .filter(|(_, div)| hashes.insert(hash_division(div, None)))
+      .map(|(corners, div)| CornerAssignment {
+        corners,
+        no_labelling: explain_no_labelling(&div),
+        div,
+      })
+      .collect()
+  }
+}
+
+/// Finds a rectangular layout for a room adjacency graph, trying each way of assigning the borders
+/// to the outside rooms until one has a labelling. Room `i` becomes region `i`.
+pub fn rectangular_dual(
+  rooms: usize,
+  edges: &[(usize, usize)],
+  layout_fn: fn(&Division, &EdgeLabels) -> Layout,
+) -> Result<(Division, EdgeLabels, Layout), DualError> {
+  let embedding = embed_graph(rooms, edges)?;
+  for corners in embedding.corner_assignments() {
+    let div = embedding.division(corners);
+    if let Some(edge_labels) = label_edges(&div).next() {
+      let layout = layout_fn(&div, &edge_labels);
+      return Ok((div, edge_labels, layout));
+    }
+  }
+  Err(DualError::NoLabelling)
+}
@@ -0,0 +1,45 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::*;
+
+/// The nodes touching each side of each region, indexed like the borders (`0` is the side facing
+/// `b0`, i.e. the bottom, `1` the left, `2` the top, and `3` the right). Each side is listed in
+/// the clockwise order of the region's connected nodes.
+pub type RegionSides = HashMap<Node, [Vec<Node>; 4]>;
+
+pub fn region_sides(div: &Division, edge_labels: &EdgeLabels) -> RegionSides {
+  let mut sides = HashMap::new();
+  let mut node_queue = VecDeque::new();
+  for node in div[Node::border(0)].iter() {
+    if node.is_region() {
+      node_queue.push_back((node, Node::border(0)));
+    }
+  }
+  while let Some((node, last)) = node_queue.pop_front() {
+    if sides.contains_key(&node) {
+      continue;
+    }
+    let mut vecs = classify_connected_nodes(node, div, edge_labels).vecs;
+    debug_assert_eq!(vecs.len(), 4);
+    let bottom = vecs
+      .iter()
+      .position(|(vec, label)| label == &Some(true) && vec.contains(&last))
+      .unwrap();
+    vecs.rotate_left(bottom);
+    let mut vecs = vecs.into_iter().map(|(vec, _)| vec);
+    let node_sides = [
+      vecs.next().unwrap(),
+      vecs.next().unwrap(),
+      vecs.next().unwrap(),
+      vecs.next().unwrap(),
+    ];
+    for &next_node in &node_sides[2] {
+      if next_node.is_region() {
+        node_queue.push_back((next_node, node));
+      }
+    }
+    sides.insert(node, node_sides);
+  }
+  debug_assert_eq!(sides.len(), div.num_regions() as usize);
+  sides
+}
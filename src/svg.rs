@@ -1,48 +1,28 @@
 use crate::*;
-use std::{collections::HashSet, fmt::Write};
+use chashmap::CHashMap;
+use std::fmt::Write;
+
+const MAX_ROW_WIDTH: usize = 5;
+const SQUARE_SIZE: f64 = 100.;
+const PADDING: f64 = 10.;
 
 pub fn generate_svg(
   divs: CHashMap<u64, Division>,
   oeis_mode: bool,
   oeis_count: CHashMap<u64, ()>,
+  layout_fn: fn(&Division, &EdgeLabels) -> Layout,
 ) -> String {
   let count = if oeis_mode {
     oeis_count.len()
   } else {
     divs.len()
   };
-  let max_row_width = 5;
-  let square_size = 100.;
-  let padding = 10.;
-  let width = std::cmp::min(count, max_row_width);
-  let height = (count + max_row_width - 1) / max_row_width;
-  let mut str = format!(
-    r#"<svg viewBox="0 0 {} {}" xmlns="http://www.w3.org/2000/svg" style="height: auto">"#,
-    width as f64 * (square_size + padding) + padding,
-    height as f64 * (square_size + padding) + padding
-  );
+  let mut str = svg_start(count);
   let mut i = 0;
-  for (_, div) in divs.into_iter() {
-    let mut hashes = HashSet::new();
-    for edge_labels in label_edges(&div) {
-      let edge_labels_hash = hash_division(&div, Some(&edge_labels));
-      if !hashes.insert(edge_labels_hash) {
-        continue;
-      }
+  for (_, div) in sorted_divisions(divs) {
+    for (edge_labels_hash, edge_labels) in sorted_labellings(&div) {
       write!(str, r#"<g id="{:?}">"#, edge_labels_hash).unwrap();
-      let layout = generate_layout(&div, &edge_labels);
-      for rect in layout {
-        let x = i % max_row_width;
-        let y = i / max_row_width;
-        write!(
-          str,
-          r#"<rect x="{}" width="{}" y="{}" height="{}" stroke="black" stroke-width="2" fill="none"/>"#,
-          rect.x1 * square_size + x as f64 * (square_size + padding) + padding,
-          rect.width() * square_size,
-          rect.y1 * square_size + y as f64 * (square_size + padding) + padding,
-          rect.height() * square_size,
-        ).unwrap();
-      }
+      write_cell(&mut str, i, &div, &edge_labels, layout_fn);
       write!(str, r#"</g>"#).unwrap();
       i += 1;
       if !oeis_mode {
@@ -53,3 +33,77 @@ pub fn generate_svg(
   str += "</svg>";
   str
 }
+
+/// Like `generate_svg`, for the output of `symmetric_dissections`, with the symmetry group of each.
+pub fn generate_symmetric_svg(
+  dissections: &[SymmetricDissection],
+  layout_fn: fn(&Division, &EdgeLabels) -> Layout,
+) -> String {
+  let count = dissections
+    .iter()
+    .map(|(_, labellings)| labellings.len())
+    .sum();
+  let mut str = svg_start(count);
+  let mut i = 0;
+  for (div, labellings) in dissections {
+    for (group, edge_labels_hash, edge_labels) in labellings {
+      write!(
+        str,
+        r#"<g id="{:?}" data-symmetry="{}">"#,
+        edge_labels_hash,
+        group.name()
+      )
+      .unwrap();
+      write_cell(&mut str, i, div, edge_labels, layout_fn);
+      write!(str, r#"</g>"#).unwrap();
+      i += 1;
+    }
+  }
+  str += "</svg>";
+  str
+}
+
+fn svg_start(count: usize) -> String {
+  let width = std::cmp::min(count, MAX_ROW_WIDTH);
+  let height = (count + MAX_ROW_WIDTH - 1) / MAX_ROW_WIDTH;
+  format!(
+    r#"<svg viewBox="0 0 {} {}" xmlns="http://www.w3.org/2000/svg" style="height: auto">"#,
+    width as f64 * (SQUARE_SIZE + PADDING) + PADDING,
+    height as f64 * (SQUARE_SIZE + PADDING) + PADDING
+  )
+}
+
+/// Draws the `i`th dissection of the grid in its canonical orientation.
+fn write_cell(
+  str: &mut String,
+  i: usize,
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  layout_fn: fn(&Division, &EdgeLabels) -> Layout,
+) {
+  let transform = canonical_transform(div, Some(edge_labels));
+  let layout = layout_fn(&div.transform(transform), &edge_labels.transform(transform));
+  let x = i % MAX_ROW_WIDTH;
+  let y = i / MAX_ROW_WIDTH;
+  write_layout_rects(
+    str,
+    &layout,
+    x as f64 * (SQUARE_SIZE + PADDING) + PADDING,
+    y as f64 * (SQUARE_SIZE + PADDING) + PADDING,
+    SQUARE_SIZE,
+  );
+}
+
+pub fn write_layout_rects(str: &mut String, layout: &Layout, x: f64, y: f64, square_size: f64) {
+  for rect in layout {
+    write!(
+      str,
+      r#"<rect x="{}" width="{}" y="{}" height="{}" stroke="black" stroke-width="2" fill="none"/>"#,
+      rect.x1 * square_size + x,
+      rect.width() * square_size,
+      rect.y1 * square_size + y,
+      rect.height() * square_size,
+    )
+    .unwrap();
+  }
+}
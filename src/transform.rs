@@ -0,0 +1,238 @@
+use crate::*;
+
+/// A symmetry of the square: reflecting top to bottom if `reflected`, then turning clockwise
+/// `quarter_turns` times. Borders are numbered clockwise from the bottom, so `b{k}` becomes
+/// `b{border(k)}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transform {
+  pub reflected: bool,
+  pub quarter_turns: u16,
+}
+
+impl Transform {
+  pub const IDENTITY: Transform = Transform {
+    reflected: false,
+    quarter_turns: 0,
+  };
+
+  pub const ALL: [Transform; 8] = [
+    Transform::turn(0),
+    Transform::turn(1),
+    Transform::turn(2),
+    Transform::turn(3),
+    Transform::reflect_then_turn(0),
+    Transform::reflect_then_turn(1),
+    Transform::reflect_then_turn(2),
+    Transform::reflect_then_turn(3),
+  ];
+
+  pub const fn turn(quarter_turns: u16) -> Transform {
+    Transform {
+      reflected: false,
+      quarter_turns: quarter_turns % 4,
+    }
+  }
+
+  pub const fn reflect_then_turn(quarter_turns: u16) -> Transform {
+    Transform {
+      reflected: true,
+      quarter_turns: quarter_turns % 4,
+    }
+  }
+
+  /// The border the `k`th border is moved to.
+  pub const fn border(&self, k: u16) -> u16 {
+    let k = if self.reflected { 6 - k % 4 } else { k };
+    (k + self.quarter_turns) % 4
+  }
+
+  /// The transform from the borders the `k`th border is moved to, which is enough to tell them
+  /// apart.
+  pub fn from_borders(border: impl Fn(u16) -> u16) -> Transform {
+    let (bottom, left) = (border(0), border(1));
+    // Turning clockwise takes the left to the border after the bottom, and reflecting to the one
+    // before it.
+    if left == (bottom + 1) % 4 {
+      Transform::turn(bottom)
+    } else {
+      Transform::reflect_then_turn(bottom + 2)
+    }
+  }
+
+  /// This transform followed by `other`.
+  pub fn then(&self, other: Transform) -> Transform {
+    Transform::from_borders(|k| other.border(self.border(k)))
+  }
+
+  pub fn inverse(&self) -> Transform {
+    Transform::from_borders(|k| (0..4).find(|&j| self.border(j) == k).unwrap())
+  }
+
+  /// The node `node` becomes, with regions keeping their numbers.
+  pub const fn node(&self, node: Node) -> Node {
+    if node.is_border() {
+      Node::border(self.border(node.0))
+    } else {
+      node
+    }
+  }
+
+  /// Whether vertical walls become horizontal.
+  pub const fn swaps_orientation(&self) -> bool {
+    self.quarter_turns % 2 == 1
+  }
+}
+
+/// Dissections and their parts which can be moved by a symmetry of the square.
+pub trait Transformable: Sized {
+  fn transform(&self, transform: Transform) -> Self;
+
+  /// A quarter turn clockwise.
+  fn rotate90(&self) -> Self {
+    self.transform(Transform::turn(1))
+  }
+
+  /// Reflects top to bottom.
+  fn reflect(&self) -> Self {
+    self.transform(Transform::reflect_then_turn(0))
+  }
+}
+
+/// Moves the borders, and reverses the order of connected nodes when reflecting. Regions keep their
+/// numbers.
+impl Transformable for Division {
+  fn transform(&self, transform: Transform) -> Division {
+    let mut connected_nodes = vec![vec![]; self.nodes().len()];
+    for node in self.nodes() {
+      let mut nodes = self[node]
+        .iter()
+        .map(|x| transform.node(x))
+        .collect::<Vec<_>>();
+      if transform.reflected {
+        nodes.reverse();
+      }
+      let node = transform.node(node);
+      // A border's connected nodes start with the next border.
+      if node.is_border() {
+        let start = nodes
+          .iter()
+          .position(|&x| x == Node::border(node.0 + 1))
+          .unwrap();
+        nodes.rotate_left(start);
+      }
+      connected_nodes[node.0 as usize] = nodes;
+    }
+    Division::from_connected_nodes(&connected_nodes).unwrap()
+  }
+}
+
+/// Moves the borders, and swaps vertical and horizontal walls when turning a quarter.
+impl Transformable for EdgeLabels {
+  fn transform(&self, transform: Transform) -> EdgeLabels {
+    self
+      .iter()
+      .map(|(&UnorderedPair(a, b), &label)| {
+        (
+          UnorderedPair(transform.node(a), transform.node(b)),
+          label != transform.swaps_orientation(),
+        )
+      })
+      .collect()
+  }
+}
+
+/// Moves the rects within their bounding box, which is turned around its top left corner.
+impl Transformable for Layout {
+  fn transform(&self, transform: Transform) -> Layout {
+    if self.is_empty() {
+      return vec![];
+    }
+    let x1 = self
+      .iter()
+      .map(|rect| rect.x1)
+      .fold(f64::INFINITY, f64::min);
+    let y1 = self
+      .iter()
+      .map(|rect| rect.y1)
+      .fold(f64::INFINITY, f64::min);
+    let x2 = self
+      .iter()
+      .map(|rect| rect.x2)
+      .fold(f64::NEG_INFINITY, f64::max);
+    let y2 = self
+      .iter()
+      .map(|rect| rect.y2)
+      .fold(f64::NEG_INFINITY, f64::max);
+    self
+      .iter()
+      .map(|rect| {
+        let mut rect = if transform.reflected {
+          Rect {
+            x1: rect.x1,
+            y1: y1 + y2 - rect.y2,
+            x2: rect.x2,
+            y2: y1 + y2 - rect.y1,
+          }
+        } else {
+          *rect
+        };
+        let (mut width, mut height) = (x2 - x1, y2 - y1);
+        for _ in 0..transform.quarter_turns {
+          // The left side becomes the top.
+          rect = Rect {
+            x1: x1 + height - (rect.y2 - y1),
+            y1: y1 + (rect.x1 - x1),
+            x2: x1 + height - (rect.y1 - y1),
+            y2: y1 + (rect.x2 - x1),
+          };
+          std::mem::swap(&mut width, &mut height);
+        }
+        rect
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use crate::*;
+
+  #[test]
+  fn transforms_keep_dissections_valid() {
+    let mut divs = HashMap::from([(0, Division::default())]);
+    for _ in 2..=6 {
+      let mut children = HashMap::new();
+      for div in divs.values() {
+        for child in divide(div) {
+          children.entry(hash_division(&child, None)).or_insert(child);
+        }
+      }
+      divs = children;
+      for div in divs.values() {
+        for edge_labels in label_edges(div) {
+          let layout = generate_layout(div, &edge_labels);
+          for transform in Transform::ALL {
+            let div = div.transform(transform);
+            let edge_labels = edge_labels.transform(transform);
+            let layout = layout.transform(transform);
+            assert_eq!(div.validate(), Ok(()), "{:?}", transform);
+            assert_eq!(
+              validate_edge_labels(&div, &edge_labels),
+              Ok(()),
+              "{:?}",
+              transform
+            );
+            assert_eq!(
+              validate_layout(&div, &edge_labels, &layout),
+              Ok(()),
+              "{:?}",
+              transform
+            );
+          }
+        }
+      }
+    }
+  }
+}
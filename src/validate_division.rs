@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DivisionError {
+  TooManyConnections {
+    node: Node,
+    len: u16,
+  },
+  UnknownNode {
+    node: Node,
+    connected_node: Node,
+  },
+  SelfConnection(Node),
+  DuplicateConnection(Node, Node),
+  /// The second node is connected to the first, but not the other way around.
+  OneWayConnection(Node, Node),
+  /// A border's connected nodes don't start with the next border and end with the previous one.
+  BorderOrder(Node),
+  /// A face other than the outside which isn't a triangle or a four-way junction. This is also
+  /// what a rotation in the wrong direction looks like.
+  Face(Vec<Node>),
+  NotPlanar,
+  /// Three connected nodes which don't form a face.
+  SeparatingTriangle([Node; 3]),
+}
+
+impl Division {
+  /// Checks that the division is a consistent rotation system: connections are symmetric, the
+  /// four borders form the outside, and every other face is a triangle or a four-way junction,
+  /// going clockwise, with no separating triangles.
+  pub fn validate(&self) -> Result<(), DivisionError> {
+    let nodes = self.num_regions() + 4;
+    for node in self.nodes() {
+      let len = self[node].len();
+      if len > self.max_connections() {
+        return Err(DivisionError::TooManyConnections { node, len });
+      }
+      let mut seen = HashSet::new();
+      for connected_node in self[node].iter() {
+        if connected_node.0 >= nodes {
+          return Err(DivisionError::UnknownNode {
+            node,
+            connected_node,
+          });
+        }
+        if connected_node == node {
+          return Err(DivisionError::SelfConnection(node));
+        }
+        if !seen.insert(connected_node) {
+          return Err(DivisionError::DuplicateConnection(node, connected_node));
+        }
+      }
+    }
+    for node in self.nodes() {
+      for connected_node in self[node].iter() {
+        if !self[connected_node].contains_item(node) {
+          return Err(DivisionError::OneWayConnection(connected_node, node));
+        }
+      }
+    }
+
+    for n in 0..4 {
+      let border = Node::border(n);
+      let connected_nodes = self[border].iter().collect::<Vec<_>>();
+      if connected_nodes.len() < 3
+        || connected_nodes[0] != Node::border(n + 1)
+        || connected_nodes[connected_nodes.len() - 1] != Node::border(n + 3)
+        || connected_nodes[1..connected_nodes.len() - 1]
+          .iter()
+          .any(|node| node.is_border())
+      {
+        return Err(DivisionError::BorderOrder(border));
+      }
+    }
+
+    // Walking along an edge, the face on its left continues to the node before it around the node
+    // walked to.
+    let mut walked = HashSet::new();
+    let mut faces = 0;
+    let mut edges = 0;
+    for a in self.nodes() {
+      for b in self[a].iter() {
+        edges += 1;
+        if walked.contains(&(a, b)) {
+          continue;
+        }
+        let mut face = vec![];
+        let (mut from, mut to) = (a, b);
+        while walked.insert((from, to)) {
+          face.push(from);
+          let next = self[to].get_item_before(from);
+          from = to;
+          to = next;
+        }
+        faces += 1;
+        let outside = face.iter().all(|node| node.is_border());
+        if (from, to) != (a, b) || !(outside || face.len() == 3 || face.len() == 4) {
+          return Err(DivisionError::Face(face));
+        }
+      }
+    }
+    if nodes as usize + faces != edges / 2 + 2 {
+      return Err(DivisionError::NotPlanar);
+    }
+
+    for a in self.nodes() {
+      for b in self[a].iter().filter(|&b| b > a) {
+        for c in self[b].iter().filter(|&c| c > b) {
+          if self[a].contains_item(c) && !is_face(self, a, b, c) {
+            return Err(DivisionError::SeparatingTriangle([a, b, c]));
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+pub fn is_face(div: &Division, a: Node, b: Node, c: Node) -> bool {
+  (div[a].get_item_after(b) == c && div[b].get_item_before(a) == c)
+    || (div[a].get_item_after(c) == b && div[c].get_item_before(a) == b)
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutError {
+  RectCount {
+    expected: usize,
+    found: usize,
+  },
+  NotFinite(Node),
+  NonPositiveArea(Node),
+  OutOfBounds(Node),
+  Overlap(Node, Node),
+  /// The rects don't overlap, but leave this much of the unit square uncovered.
+  Gap(f64),
+  /// The nodes are connected in the division, but don't touch along a wall.
+  MissingContact(Node, Node),
+  /// The nodes touch along a wall, but aren't connected in the division.
+  UnexpectedContact(Node, Node),
+  MissingLabel(Node, Node),
+  /// The wall between the nodes is horizontal iff `horizontal`, but the edge is labeled otherwise.
+  WrongOrientation {
+    edge: UnorderedPair<Node>,
+    horizontal: bool,
+  },
+}
+
+const EPSILON: f64 = 1e-9;
+
+/// Checks that the layout exactly tiles the unit square with rects of positive area, and that
+/// which rects touch, and along which kind of wall, matches `div` and `edge_labels` in the
+/// orientation of a `Layout`.
+pub fn validate_layout(
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  layout: &Layout,
+) -> Result<(), LayoutError> {
+  if layout.len() != div.num_regions() as usize {
+    return Err(LayoutError::RectCount {
+      expected: div.num_regions() as usize,
+      found: layout.len(),
+    });
+  }
+  let rects = div.regions().zip(layout).collect::<Vec<_>>();
+  for &(region, rect) in &rects {
+    if ![rect.x1, rect.y1, rect.x2, rect.y2]
+      .iter()
+      .all(|x| x.is_finite())
+    {
+      return Err(LayoutError::NotFinite(region));
+    }
+    if rect.width() <= EPSILON || rect.height() <= EPSILON {
+      return Err(LayoutError::NonPositiveArea(region));
+    }
+    if rect.x1 < -EPSILON || rect.y1 < -EPSILON || rect.x2 > 1. + EPSILON || rect.y2 > 1. + EPSILON
+    {
+      return Err(LayoutError::OutOfBounds(region));
+    }
+  }
+
+  // Whether each pair of touching nodes is separated by a horizontal wall.
+  let mut contacts = HashMap::new();
+  for (i, &(a, a_rect)) in rects.iter().enumerate() {
+    for &(border, touching) in &[
+      (0, (a_rect.y2 - 1.).abs() < EPSILON),
+      (1, a_rect.x1.abs() < EPSILON),
+      (2, a_rect.y1.abs() < EPSILON),
+      (3, (a_rect.x2 - 1.).abs() < EPSILON),
+    ] {
+      if touching {
+        contacts.insert(UnorderedPair(a, Node::border(border)), border % 2 == 0);
+      }
+    }
+    for &(b, b_rect) in &rects[i + 1..] {
+      let x_overlap = a_rect.x2.min(b_rect.x2) - a_rect.x1.max(b_rect.x1);
+      let y_overlap = a_rect.y2.min(b_rect.y2) - a_rect.y1.max(b_rect.y1);
+      if x_overlap > EPSILON && y_overlap > EPSILON {
+        return Err(LayoutError::Overlap(a, b));
+      }
+      if y_overlap.abs() < EPSILON && x_overlap > EPSILON {
+        contacts.insert(UnorderedPair(a, b), true);
+      } else if x_overlap.abs() < EPSILON && y_overlap > EPSILON {
+        contacts.insert(UnorderedPair(a, b), false);
+      }
+    }
+  }
+  let uncovered = 1.
+    - layout
+      .iter()
+      .map(|rect| rect.width() * rect.height())
+      .sum::<f64>();
+  if uncovered > EPSILON {
+    return Err(LayoutError::Gap(uncovered));
+  }
+
+  for a in div.nodes() {
+    for b in div[a].iter() {
+      let edge = UnorderedPair(a, b);
+      if a.is_border() && b.is_border() || a > b {
+        continue;
+      }
+      let horizontal = *contacts
+        .get(&edge)
+        .ok_or(LayoutError::MissingContact(a, b))?;
+      let label = *edge_labels
+        .get(&edge)
+        .ok_or(LayoutError::MissingLabel(a, b))?;
+      if label != horizontal {
+        return Err(LayoutError::WrongOrientation { edge, horizontal });
+      }
+    }
+  }
+  for edge in contacts.keys() {
+    if !div[edge.0].contains_item(edge.1) {
+      return Err(LayoutError::UnexpectedContact(edge.0, edge.1));
+    }
+  }
+  Ok(())
+}
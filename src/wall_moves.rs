@@ -0,0 +1,241 @@
+use crate::*;
+
+/// The rooms or borders along one side of the rectangle formed by two rooms sharing a whole wall,
+/// clockwise around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WallSides {
+  /// The sides parallel to the wall, beyond `a` and beyond `b`.
+  pub beyond_a: Vec<Node>,
+  pub beyond_b: Vec<Node>,
+  /// The sides across the ends of the wall. `after_a` comes after `beyond_a` going clockwise.
+  pub after_a: Vec<Node>,
+  pub after_b: Vec<Node>,
+}
+
+fn connected_nodes(div: &Division) -> Vec<Vec<Node>> {
+  div
+    .nodes()
+    .map(|node| div[node].iter().collect::<Vec<_>>())
+    .collect()
+}
+
+/// The nodes on either side of the edge `a`-`b`, if both of its faces are triangles.
+fn apexes(div: &Division, a: Node, b: Node) -> Option<(Node, Node)> {
+  let after = div[a].get_item_after(b);
+  let before = div[a].get_item_before(b);
+  if after == before || div[b].get_item_before(a) != after || div[b].get_item_after(a) != before {
+    return None;
+  }
+  Some((after, before))
+}
+
+/// The rectangulations where `a` and `b` no longer touch, and the rooms or borders at either end of
+/// their wall do instead. These are slides, where the walls ending on theirs from either side move
+/// past each other, and rotations of the short wall between those ends, possibly taking one of
+/// them along, which turns one other wall between the four of them around.
+pub fn wall_flips(
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  a: Node,
+  b: Node,
+) -> Vec<(Division, EdgeLabels)> {
+  let (c, d) = match apexes(div, a, b) {
+    Some(apexes) if !(a.is_border() && b.is_border()) => apexes,
+    _ => return vec![],
+  };
+  if (c.is_border() && d.is_border()) || div[c].contains_item(d) {
+    return vec![];
+  }
+  let mut connected_nodes = connected_nodes(div);
+  connected_nodes[a.0 as usize].retain(|&x| x != b);
+  connected_nodes[b.0 as usize].retain(|&x| x != a);
+  for &(node, new_node) in &[(c, d), (d, c)] {
+    let nodes = &mut connected_nodes[node.0 as usize];
+    let i = nodes.iter().position(|&x| x == a).unwrap();
+    let j = nodes.iter().position(|&x| x == b).unwrap();
+    // `a` and `b` are next to each other, possibly wrapping around.
+    let at = if (i + 1) % nodes.len() == j {
+      i + 1
+    } else {
+      j + 1
+    };
+    nodes.insert(at, new_node);
+  }
+  let flipped = Division::from_connected_nodes(&connected_nodes).unwrap();
+
+  let mut flips = vec![];
+  for &label in &[true, false] {
+    // A border's walls all go the same way.
+    if [c, d]
+      .iter()
+      .any(|&x| x.is_border() && label != (x.0 % 2 == 0))
+    {
+      continue;
+    }
+    let mut new_labels = edge_labels.clone();
+    new_labels.remove(&UnorderedPair(a, b));
+    new_labels.insert(UnorderedPair(c, d), label);
+    for &turned in &[None, Some((a, c)), Some((c, b)), Some((b, d)), Some((d, a))] {
+      let mut new_labels = new_labels.clone();
+      if let Some((x, y)) = turned {
+        if x.is_border() || y.is_border() {
+          continue;
+        }
+        let label = new_labels.get_mut(&UnorderedPair(x, y)).unwrap();
+        *label = !*label;
+      }
+      if is_labelling_around(&flipped, &new_labels, &[a, b, c, d]) {
+        flips.push((
+          Division::from_connected_nodes(&connected_nodes).unwrap(),
+          new_labels,
+        ));
+      }
+    }
+  }
+  flips
+}
+
+/// Whether the regions among `nodes` have two sides of each label, and no face touching them has
+/// all its walls going the same way. Labellings of divisions without four-way junctions are
+/// exactly those for which this holds everywhere.
+fn is_labelling_around(div: &Division, edge_labels: &EdgeLabels, nodes: &[Node]) -> bool {
+  let label = |x: Node, y: Node| edge_labels.get(&UnorderedPair(x, y)).copied();
+  nodes.iter().all(|&x| {
+    let classification = classify_connected_nodes(x, div, edge_labels);
+    (x.is_border() || (classification.true_vecs_count == 2 && classification.false_vecs_count == 2))
+      && div[x].iter().all(|y| {
+        let z = div[x].get_item_after(y);
+        !is_face(div, x, y, z) || label(x, y) != label(y, z) || label(y, z) != label(z, x)
+      })
+  })
+}
+
+/// The sides of the rectangle formed by `a` and `b`, if they share a whole wall.
+pub fn wall_sides(div: &Division, edge_labels: &EdgeLabels, a: Node, b: Node) -> Option<WallSides> {
+  if !a.is_region() || !b.is_region() || !div[a].contains_item(b) {
+    return None;
+  }
+  let (c, d) = apexes(div, a, b)?;
+  let label = |x: Node, y: Node| edge_labels[&UnorderedPair(x, y)];
+  let wall = label(a, b);
+  if [label(c, a), label(c, b), label(d, a), label(d, b)].contains(&wall) {
+    return None;
+  }
+  // Going clockwise around `a` from `b`, its other sides are across from the wall, parallel to
+  // it, and across from it again.
+  let split = |node: Node, other: Node| {
+    let nodes = div[node]
+      .iter_starting_at(other)
+      .skip(1)
+      .collect::<Vec<_>>();
+    let start = nodes.iter().position(|&x| label(node, x) == wall).unwrap();
+    let end = nodes.iter().rposition(|&x| label(node, x) == wall).unwrap();
+    (
+      nodes[..start].to_vec(),
+      nodes[start..=end].to_vec(),
+      nodes[end + 1..].to_vec(),
+    )
+  };
+  let (a_0, beyond_a, a_1) = split(a, b);
+  let (b_0, beyond_b, b_1) = split(b, a);
+  Some(WallSides {
+    beyond_a,
+    beyond_b,
+    after_a: [&a_1[..], &b_0[1..]].concat(),
+    after_b: [&b_1[..], &a_0[1..]].concat(),
+  })
+}
+
+/// Rotates the wall shared by the whole of `a` and `b` by a quarter turn, clockwise if `clockwise`.
+/// The new wall ends on `ends[0]` and `ends[1]`, which were beyond `a` and `b`.
+pub fn rotate_wall(
+  div: &Division,
+  edge_labels: &EdgeLabels,
+  a: Node,
+  b: Node,
+  clockwise: bool,
+  ends: [Node; 2],
+) -> Option<(Division, EdgeLabels)> {
+  let sides = wall_sides(div, edge_labels, a, b)?;
+  let wall = edge_labels[&UnorderedPair(a, b)];
+  let s = sides.beyond_a.iter().position(|&x| x == ends[0])?;
+  let t = sides.beyond_b.iter().position(|&x| x == ends[1])?;
+  // Going clockwise around the rectangle, `first` takes the side after `beyond_a`, and `second`
+  // the side after `beyond_b`.
+  let (first, second) = if clockwise { (a, b) } else { (b, a) };
+  let first_nodes = [
+    &sides.beyond_a[s..],
+    &sides.after_a[..],
+    &sides.beyond_b[..=t],
+    &[second][..],
+  ]
+  .concat();
+  let second_nodes = [
+    &sides.beyond_b[t..],
+    &sides.after_b[..],
+    &sides.beyond_a[..=s],
+    &[first][..],
+  ]
+  .concat();
+
+  let mut edge_labels = edge_labels.clone();
+  edge_labels.retain(|&UnorderedPair(x, y), _| ![a, b].contains(&x) && ![a, b].contains(&y));
+  edge_labels.insert(UnorderedPair(first, second), !wall);
+  for &(node, parallel, across) in &[
+    (
+      first,
+      [&sides.beyond_a[s..], &sides.beyond_b[..=t]],
+      &sides.after_a,
+    ),
+    (
+      second,
+      [&sides.beyond_b[t..], &sides.beyond_a[..=s]],
+      &sides.after_b,
+    ),
+  ] {
+    for &x in parallel.concat().iter() {
+      edge_labels.insert(UnorderedPair(node, x), wall);
+    }
+    for &x in across {
+      edge_labels.insert(UnorderedPair(node, x), !wall);
+    }
+  }
+
+  // Each neighbour meets `first` and `second` in the opposite order to going clockwise around the
+  // rectangle.
+  let mut connected_nodes = connected_nodes(div);
+  for &x in first_nodes.iter().chain(&second_nodes) {
+    if x == a || x == b {
+      continue;
+    }
+    let met = if x == ends[0] {
+      vec![first, second]
+    } else if x == ends[1] {
+      vec![second, first]
+    } else if first_nodes.contains(&x) {
+      vec![first]
+    } else {
+      vec![second]
+    };
+    let nodes = &mut connected_nodes[x.0 as usize];
+    let len = nodes.len();
+    let start = (0..len)
+      .find(|&i| [a, b].contains(&nodes[i]) && ![a, b].contains(&nodes[(i + len - 1) % len]))
+      .unwrap();
+    let count = (0..len)
+      .take_while(|&i| [a, b].contains(&nodes[(start + i) % len]))
+      .count();
+    nodes.rotate_left(start);
+    nodes.splice(..count, met);
+    // Borders' rotations start from the next border, so put them back where they started.
+    if start + count <= len {
+      nodes.rotate_right(start);
+    }
+  }
+  connected_nodes[first.0 as usize] = first_nodes;
+  connected_nodes[second.0 as usize] = second_nodes;
+  Some((
+    Division::from_connected_nodes(&connected_nodes).unwrap(),
+    edge_labels,
+  ))
+}